@@ -0,0 +1,87 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use rand::Rng;
+use rl2020::{lookup_status, Bitstring, RevocationList2020, RevocationStatus};
+
+const SIZE_KB: usize = 128;
+
+// random_list returns the largest list with about a tenth of the indexes revoked
+fn random_list(id: &str) -> RevocationList2020 {
+    let mut rl = RevocationList2020::new(id, SIZE_KB).unwrap();
+    let mut r = rand::thread_rng();
+    let indexes: Vec<u64> = (0..rl.capacity() / 10)
+        .map(|_| r.gen_range(0..rl.capacity()) as u64)
+        .collect();
+    rl.update_many(RevocationStatus::Revoke, &indexes).unwrap();
+    rl
+}
+
+fn bench_bitset(c: &mut Criterion) {
+    let a = random_list("bench");
+    let b = random_list("bench");
+
+    c.bench_function("revoked_count", |bench| {
+        bench.iter(|| black_box(&a).revoked_count())
+    });
+    c.bench_function("revoked_indexes", |bench| {
+        bench.iter(|| black_box(&a).revoked_indexes().count())
+    });
+    c.bench_function("diff", |bench| {
+        bench.iter(|| black_box(&a).diff(black_box(&b)).unwrap())
+    });
+    c.bench_function("union", |bench| {
+        bench.iter(|| black_box(&a).union(black_box(&b)).unwrap())
+    });
+    c.bench_function("merge", |bench| {
+        bench.iter_batched(
+            || a.clone(),
+            |mut rl| rl.merge(&b).unwrap(),
+            BatchSize::LargeInput,
+        )
+    });
+
+    let mut r = rand::thread_rng();
+    let indexes: Vec<u64> = (0..10_000)
+        .map(|_| r.gen_range(0..a.capacity()) as u64)
+        .collect();
+    c.bench_function("update_many", |bench| {
+        bench.iter_batched(
+            || a.clone(),
+            |mut rl| rl.update_many(RevocationStatus::Revoke, &indexes).unwrap(),
+            BatchSize::LargeInput,
+        )
+    });
+
+    let mut bs = Bitstring::new(SIZE_KB).unwrap();
+    for i in a.revoked_indexes() {
+        bs.update(RevocationStatus::Revoke, i).unwrap();
+    }
+    c.bench_function("bitstring_get", |bench| {
+        bench.iter(|| {
+            indexes
+                .iter()
+                .filter(|i| bs.get(**i).unwrap() == RevocationStatus::Revoke)
+                .count()
+        })
+    });
+}
+
+fn bench_stream(c: &mut Criterion) {
+    let rl = random_list("bench");
+    let json: serde_json::Value = serde_json::from_str(&rl.to_string()).unwrap();
+    let encoded = json["encodedList"].as_str().unwrap().to_owned();
+
+    c.bench_function("decode", |bench| {
+        bench.iter(|| Bitstring::decode(black_box(&encoded)).unwrap())
+    });
+    // the lookup stops decompressing at the byte of the index
+    c.bench_function("lookup_status_first", |bench| {
+        bench.iter(|| lookup_status(&mut black_box(&encoded).as_bytes(), 42).unwrap())
+    });
+    c.bench_function("lookup_status_last", |bench| {
+        let last = rl.capacity() as u64 - 1;
+        bench.iter(|| lookup_status(&mut black_box(&encoded).as_bytes(), last).unwrap())
+    });
+}
+
+criterion_group!(benches, bench_bitset, bench_stream);
+criterion_main!(benches);
@@ -0,0 +1,3 @@
+// The shared library of the bindings, the exported functions are
+// defined by the rl2020 crate according to the enabled features
+pub use rl2020_lib::*;
@@ -0,0 +1,21 @@
+// generates the C header of the ffi bindings in $OUT_DIR/rl2020.h,
+// the copy in include/rl2020.h is checked against it by test_ffi
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(feature = "ffi")]
+    {
+        use std::env;
+        use std::path::Path;
+
+        let var = |name: &str| {
+            env::var(name).map_err(|e| format!("cannot read {} from the environment: {}", name, e))
+        };
+        let dir = var("CARGO_MANIFEST_DIR")?;
+        let out = Path::new(&var("OUT_DIR")?).join("rl2020.h");
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        cbindgen::generate(&dir)
+            .map_err(|e| format!("unable to generate the C header: {}", e))?
+            .write_to_file(out);
+    }
+    Ok(())
+}
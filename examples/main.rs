@@ -1,5 +1,4 @@
 use rl2020::{CredentialError, CredentialStatus, RevocationList2020};
-use serde_derive::{Deserialize, Serialize};
 
 fn main() -> Result<(), CredentialError> {
     println!("Hello, RevocationList2020!");
@@ -10,7 +9,7 @@ fn main() -> Result<(), CredentialError> {
 
     // create a credential that uses the revocation list
     let c = create_credential();
-    let c_idx = c.credential_status.revocation_list_index;
+    let c_idx = c.coordinates().1;
 
     // check if the credential is revoked
     let revoked = rl.is_revoked(&c)?;
@@ -35,7 +34,7 @@ fn main() -> Result<(), CredentialError> {
     Ok(())
 }
 
-fn create_credential() -> VerifableCredential {
+fn create_credential() -> serde_json::Value {
     // Some JSON input data as a &str. Maybe this comes from the user.
     let data = r#"
     {
@@ -61,41 +60,7 @@ fn create_credential() -> VerifableCredential {
         },
         "proof": {}
     }"#;
-    serde_json::from_str::<VerifableCredential>(data).unwrap()
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct VerifableCredential {
-    #[serde(rename = "credentialStatus")]
-    credential_status: CredentialStatusExample,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CredentialStatusExample {
-    #[serde(rename = "id")]
-    id: String,
-    #[serde(rename = "type")]
-    typ: String,
-    #[serde(rename = "revocationListIndex")]
-    revocation_list_index: u64,
-    #[serde(rename = "revocationListCredential")]
-    revocation_list_credential: String,
-}
-
-/// To be able to use the rl2020 library it's neccessary
-/// to implement the CredentialStatus trait
-impl CredentialStatus for VerifableCredential {
-    fn coordinates(&self) -> (String, u64) {
-        (
-            self.credential_status.revocation_list_credential.to_owned(),
-            self.credential_status.revocation_list_index.to_owned(),
-        )
-    }
-
-    fn type_def(&self) -> (String, String) {
-        (
-            self.credential_status.id.to_owned(),
-            self.credential_status.typ.to_owned(),
-        )
-    }
+    // the library implements the CredentialStatus trait for raw json credentials,
+    // see also RevocationList2020Status to deserialize the status entry alone
+    serde_json::from_str::<serde_json::Value>(data).unwrap()
 }
@@ -0,0 +1,115 @@
+#ifndef RL2020_H
+#define RL2020_H
+
+/* Generated with cbindgen from src/ffi.rs, do not edit by hand */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * Rl2020Result is the result code returned by the functions of the C ABI
+ */
+typedef enum Rl2020Result {
+  RL2020_RESULT_OK = 0,
+  /**
+   * a required pointer argument is null
+   */
+  RL2020_RESULT_NULL_POINTER = 1,
+  /**
+   * a string argument is not valid utf-8
+   */
+  RL2020_RESULT_INVALID_STRING = 2,
+  /**
+   * the operation on the list failed, e.g. an invalid list or an index out of range
+   */
+  RL2020_RESULT_FAILED = 3,
+  /**
+   * the library panicked, the state of the list is unspecified
+   */
+  RL2020_RESULT_PANIC = 4,
+} Rl2020Result;
+
+typedef struct RevocationList2020 RevocationList2020;
+
+/**
+ * Returns the message of the last error that occurred on the calling thread,
+ * or null if no error occurred. The message is owned by the library and is
+ * valid until the next failing call on the same thread
+ */
+const char *rl2020_last_error(void);
+
+/**
+ * Creates an empty revocation list with the given id and size in kb
+ *
+ * # Safety
+ * `id` must be a nul terminated string and `out` a valid pointer,
+ * the list written to `out` must be released with `rl2020_list_free`
+ */
+enum Rl2020Result rl2020_list_new(const char *id, size_t size, struct RevocationList2020 **out);
+
+/**
+ * Parses a revocation list from its JSON representation
+ *
+ * # Safety
+ * `json` must be a nul terminated string and `out` a valid pointer,
+ * the list written to `out` must be released with `rl2020_list_free`
+ */
+enum Rl2020Result rl2020_list_parse(const char *json, struct RevocationList2020 **out);
+
+/**
+ * Revokes the credential at the given index
+ *
+ * # Safety
+ * `list` must be a list returned by `rl2020_list_new` or `rl2020_list_parse`
+ */
+enum Rl2020Result rl2020_list_revoke(struct RevocationList2020 *list, uint64_t index);
+
+/**
+ * Resets (un-revokes) the credential at the given index
+ *
+ * # Safety
+ * `list` must be a list returned by `rl2020_list_new` or `rl2020_list_parse`
+ */
+enum Rl2020Result rl2020_list_reset(struct RevocationList2020 *list, uint64_t index);
+
+/**
+ * Writes to `out` whether the credential at the given index is revoked
+ *
+ * # Safety
+ * `list` must be a list returned by `rl2020_list_new` or `rl2020_list_parse`
+ * and `out` a valid pointer
+ */
+enum Rl2020Result rl2020_list_is_revoked(const struct RevocationList2020 *list,
+                                         uint64_t index,
+                                         bool *out);
+
+/**
+ * Writes to `out` the JSON representation of the list
+ *
+ * # Safety
+ * `list` must be a list returned by `rl2020_list_new` or `rl2020_list_parse`
+ * and `out` a valid pointer, the string written to `out` must be released
+ * with `rl2020_string_free`
+ */
+enum Rl2020Result rl2020_list_serialize(const struct RevocationList2020 *list, char **out);
+
+/**
+ * Releases a list, passing null is a no-op
+ *
+ * # Safety
+ * `list` must be a list returned by `rl2020_list_new` or `rl2020_list_parse`
+ * that has not been released already
+ */
+void rl2020_list_free(struct RevocationList2020 *list);
+
+/**
+ * Releases a string returned by the library, passing null is a no-op
+ *
+ * # Safety
+ * `s` must be a string returned by `rl2020_list_serialize`
+ * that has not been released already
+ */
+void rl2020_string_free(char *s);
+
+#endif /* RL2020_H */
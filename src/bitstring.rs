@@ -0,0 +1,629 @@
+use crate::{
+    deflate, CredentialError, RevocationStatus, MAX_BITSTRING_SIZE_KB, MIN_BITSTRING_SIZE_KN,
+};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use base64::{decode_config, encode_config, DecodeError, STANDARD};
+use miniz_oxide::deflate::core::{
+    compress, create_comp_flags_from_zip_params, CompressionStrategy, CompressorOxide, TDEFLFlush,
+    TDEFLStatus,
+};
+use miniz_oxide::inflate::core::inflate_flags::{
+    TINFL_FLAG_PARSE_ZLIB_HEADER, TINFL_FLAG_USING_NON_WRAPPING_OUTPUT_BUF,
+};
+use miniz_oxide::inflate::core::DecompressorOxide;
+use miniz_oxide::inflate::{
+    decompress_to_vec_zlib, decompress_to_vec_zlib_with_limit, TINFLStatus,
+};
+
+// the zlib compression level, the default of zlib
+const COMPRESSION_LEVEL: u8 = 6;
+// the highest compression level, the uber level of miniz
+const MAX_COMPRESSION_LEVEL: u8 = 10;
+// the strategies tried by the best compression
+const COMPRESSION_STRATEGIES: [CompressionStrategy; 5] = [
+    CompressionStrategy::Default,
+    CompressionStrategy::Filtered,
+    CompressionStrategy::RLE,
+    CompressionStrategy::HuffmanOnly,
+    CompressionStrategy::Fixed,
+];
+
+/// Compression is the zlib compression used for the encodedList,
+/// whatever the choice the output is a valid zlib stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    /// the default zlib level
+    #[default]
+    Default,
+    /// a zlib level, from 0 (no compression) to 10 (smallest and slowest)
+    Level(u8),
+    /// tries all the levels and strategies keeping the smallest output,
+    /// much slower than the others. Lists only apply it when they are
+    /// encoded for publication, the changes use the default level
+    Best,
+    /// an encoding that only depends on the bitstring, to reproduce the same
+    /// encodedList across library versions, e.g. for signatures. It compresses
+    /// only runs of equal bytes, so it is larger than the others on dense lists
+    Deterministic,
+}
+
+impl Compression {
+    // for_updates returns the compression used to encode the changes of a
+    // list, that is the same except for the Best one, that is too slow
+    #[cfg(feature = "std")]
+    pub(crate) fn for_updates(self) -> Self {
+        match self {
+            Compression::Best => Compression::Default,
+            compression => compression,
+        }
+    }
+}
+
+/// CompressionStats reports the size in bytes of a bitstring and of its encodedList
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionStats {
+    /// the size of the bitstring
+    pub raw: usize,
+    /// the size of the zlib stream
+    pub compressed: usize,
+    /// the size of the base64 encodedList
+    pub encoded: usize,
+}
+
+impl CompressionStats {
+    /// computes the stats of the encodedList of a bitstring of raw bytes
+    pub fn new(raw: usize, encoded_list: &str) -> Self {
+        let padding = encoded_list
+            .bytes()
+            .rev()
+            .take_while(|b| *b == b'=')
+            .count();
+        CompressionStats {
+            raw,
+            compressed: (encoded_list.len() / 4 * 3).saturating_sub(padding),
+            encoded: encoded_list.len(),
+        }
+    }
+
+    /// returns the size of the zlib stream relative to the size of the bitstring
+    pub fn ratio(&self) -> f64 {
+        match self.raw {
+            0 => 0.0,
+            raw => self.compressed as f64 / raw as f64,
+        }
+    }
+}
+
+// The functions in this module only depend on core and alloc,
+// so that the bitstring can be used on targets without std
+
+pub(crate) fn check_bounds(bits: &[u8], index: u64) -> Result<(), CredentialError> {
+    let capacity = bits.len() as u64 * 8;
+    match index {
+        i if i >= capacity => Err(CredentialError::new(&format!(
+            "max indexable element is {}, provided index {} is out of range",
+            capacity, i,
+        ))),
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn get(bits: &[u8], index: u64) -> Result<RevocationStatus, CredentialError> {
+    check_bounds(bits, index)?;
+
+    let pos = (index / 8) as usize;
+    let j = (index % 8) as u8;
+
+    match bits[pos] & (1 << j) {
+        0 => Ok(RevocationStatus::Reset),
+        _ => Ok(RevocationStatus::Revoke),
+    }
+}
+
+// set changes the bit at index returning the status before the change
+pub(crate) fn set(
+    bits: &mut [u8],
+    action: RevocationStatus,
+    index: u64,
+) -> Result<RevocationStatus, CredentialError> {
+    let old = get(bits, index)?;
+
+    let pos = (index / 8) as usize;
+    let j = (index % 8) as u8;
+
+    match action {
+        RevocationStatus::Revoke => bits[pos] |= 1 << j,
+        RevocationStatus::Reset => bits[pos] &= !(1 << j),
+    };
+    Ok(old)
+}
+
+// encode compresses the bitstring with zlib and encodes it in base64
+pub(crate) fn encode(bits: &[u8]) -> Result<String, CredentialError> {
+    encode_with(bits, Compression::Default)
+}
+
+// encode_with is encode with the given compression
+pub(crate) fn encode_with(
+    bits: &[u8],
+    compression: Compression,
+) -> Result<String, CredentialError> {
+    let compressed = match compression {
+        Compression::Default => {
+            compress_zlib(bits, COMPRESSION_LEVEL, CompressionStrategy::Default)?
+        }
+        Compression::Level(level) => {
+            check_compression(compression)?;
+            compress_zlib(bits, level, CompressionStrategy::Default)?
+        }
+        Compression::Best => compress_best(bits)?,
+        Compression::Deterministic => deflate::compress_zlib(bits),
+    };
+    Ok(encode_config(&compressed, STANDARD))
+}
+
+pub(crate) fn check_compression(compression: Compression) -> Result<(), CredentialError> {
+    match compression {
+        Compression::Level(level) if level > MAX_COMPRESSION_LEVEL => {
+            Err(CredentialError::new(&format!(
+                "maximum compression level is {}, got {}",
+                MAX_COMPRESSION_LEVEL, level
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+// compress_best tries every level and strategy and keeps the smallest output
+// that decompresses back to the bitstring, the first one wins the ties
+fn compress_best(bits: &[u8]) -> Result<Vec<u8>, CredentialError> {
+    let mut best = compress_zlib(bits, COMPRESSION_LEVEL, CompressionStrategy::Default)?;
+    for level in 1..=MAX_COMPRESSION_LEVEL {
+        for strategy in COMPRESSION_STRATEGIES {
+            let candidate = compress_zlib(bits, level, strategy)?;
+            if candidate.len() < best.len()
+                && decompress_to_vec_zlib(&candidate).is_ok_and(|d| d == bits)
+            {
+                best = candidate;
+            }
+        }
+    }
+    Ok(best)
+}
+
+// zlib_compressor returns the compressor of encode, the
+// streaming encoding must use the same to produce the same output
+#[cfg(feature = "std")]
+pub(crate) fn zlib_compressor() -> CompressorOxide {
+    compressor(COMPRESSION_LEVEL, CompressionStrategy::Default)
+}
+
+fn compressor(level: u8, strategy: CompressionStrategy) -> CompressorOxide {
+    // a positive window size adds the zlib header and checksum
+    let flags = create_comp_flags_from_zip_params(level.into(), 1, strategy as i32);
+    CompressorOxide::new(flags)
+}
+
+// compress_zlib compresses the bitstring in a zlib stream
+// with the given level and strategy
+fn compress_zlib(
+    bits: &[u8],
+    level: u8,
+    strategy: CompressionStrategy,
+) -> Result<Vec<u8>, CredentialError> {
+    let mut compressor = compressor(level, strategy);
+    let mut output = vec![0; core::cmp::max(bits.len() / 2, 64)];
+    let (mut in_pos, mut out_pos) = (0, 0);
+    loop {
+        let (status, bytes_in, bytes_out) = compress(
+            &mut compressor,
+            &bits[in_pos..],
+            &mut output[out_pos..],
+            TDEFLFlush::Finish,
+        );
+        in_pos += bytes_in;
+        out_pos += bytes_out;
+        match status {
+            TDEFLStatus::Done => break,
+            // the output is full
+            TDEFLStatus::Okay => output.resize(output.len() * 2, 0),
+            // only returned for invalid parameters
+            status => {
+                return Err(CredentialError::new(&format!(
+                    "zlib compression failed: {:?}",
+                    status
+                )))
+            }
+        }
+    }
+    output.truncate(out_pos);
+    Ok(output)
+}
+
+// The bulk operations work on 64 bit words instead of single bytes. The words
+// are read in little endian order, so that bit j of the word at position w is
+// the index w * 64 + j, consistently with the bit order of get and set. The
+// count and the combination go through blocks of 8 words, with std on x86_64
+// they use popcnt and avx2 when the cpu supports them
+
+const WORD_BYTES: usize = 8;
+const BLOCK_BYTES: usize = 8 * WORD_BYTES;
+
+/// BitOp is the bitwise operation applied by combine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BitOp {
+    Or,
+    And,
+}
+
+impl BitOp {
+    #[inline(always)]
+    fn apply(self, a: u64, b: u64) -> u64 {
+        match self {
+            BitOp::Or => a | b,
+            BitOp::And => a & b,
+        }
+    }
+}
+
+#[inline(always)]
+fn word(chunk: &[u8]) -> u64 {
+    let mut w = [0u8; WORD_BYTES];
+    w[..chunk.len()].copy_from_slice(chunk);
+    u64::from_le_bytes(w)
+}
+
+// Ones iterates over the set bits of a word, returning their indexes
+struct Ones {
+    word: u64,
+    base: u64,
+}
+
+impl Iterator for Ones {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.word == 0 {
+            return None;
+        }
+        let j = self.word.trailing_zeros() as u64;
+        // clear the lowest set bit
+        self.word &= self.word - 1;
+        Some(self.base + j)
+    }
+}
+
+fn ones(word: u64, pos: usize) -> Ones {
+    Ones {
+        word,
+        base: (pos * WORD_BYTES * 8) as u64,
+    }
+}
+
+// words iterates over the bitstring 64 bits at a time, the last word
+// is padded with zeros if the length is not a multiple of 8 bytes
+fn words(bits: &[u8]) -> impl Iterator<Item = u64> + '_ {
+    let chunks = bits.chunks_exact(WORD_BYTES);
+    let remainder = chunks.remainder();
+    chunks
+        .map(word)
+        .chain((!remainder.is_empty()).then(|| word(remainder)))
+}
+
+// count_ones returns the number of bits set in the bitstring
+pub(crate) fn count_ones(bits: &[u8]) -> u64 {
+    #[cfg(all(feature = "std", target_arch = "x86_64"))]
+    if std::is_x86_feature_detected!("popcnt") {
+        // the cpu supports popcnt
+        return unsafe { count_ones_popcnt(bits) };
+    }
+    count_ones_blocks(bits, |w| w.count_ones())
+}
+
+// count_ones_blocks counts the bits 8 words at a time, with a sum for each
+// word of the block, and then the words and the bytes left
+#[inline(always)]
+pub(crate) fn count_ones_blocks(bits: &[u8], popcnt: impl Fn(u64) -> u32) -> u64 {
+    let blocks = bits.chunks_exact(BLOCK_BYTES);
+    let rest = blocks.remainder();
+    let mut sums = [0u64; BLOCK_BYTES / WORD_BYTES];
+    for block in blocks {
+        for (sum, w) in sums.iter_mut().zip(block.chunks_exact(WORD_BYTES)) {
+            *sum += popcnt(word(w)) as u64;
+        }
+    }
+    sums.iter().sum::<u64>() + words(rest).map(|w| popcnt(w) as u64).sum::<u64>()
+}
+
+#[cfg(all(feature = "std", target_arch = "x86_64"))]
+#[target_feature(enable = "popcnt")]
+unsafe fn count_ones_popcnt(bits: &[u8]) -> u64 {
+    use core::arch::x86_64::_popcnt64;
+    count_ones_blocks(bits, |w| _popcnt64(w as i64) as u32)
+}
+
+// iter_ones iterates over the indexes of the bits set, in ascending order
+pub(crate) fn iter_ones(bits: &[u8]) -> impl Iterator<Item = u64> + '_ {
+    words(bits)
+        .enumerate()
+        .filter(|(_, w)| *w != 0)
+        .flat_map(|(pos, w)| ones(w, pos))
+}
+
+// diff returns the indexes that are set in b but not in a,
+// and the ones set in a but not in b, in ascending order
+#[cfg(feature = "std")]
+pub(crate) fn diff(a: &[u8], b: &[u8]) -> (Vec<u64>, Vec<u64>) {
+    let mut set = Vec::new();
+    let mut cleared = Vec::new();
+    for (pos, (wa, wb)) in words(a).zip(words(b)).enumerate() {
+        let changed = wa ^ wb;
+        if changed == 0 {
+            continue;
+        }
+        set.extend(ones(changed & wb, pos));
+        cleared.extend(ones(changed & wa, pos));
+    }
+    (set, cleared)
+}
+
+// combine applies the bitwise operation to the two bitstrings,
+// the result is as long as the shortest one
+pub(crate) fn combine(a: &[u8], b: &[u8], op: BitOp) -> Vec<u8> {
+    let n = a.len().min(b.len());
+    let (a, b) = (&a[..n], &b[..n]);
+    let mut out = vec![0u8; n];
+    #[cfg(all(feature = "std", target_arch = "x86_64"))]
+    if std::is_x86_feature_detected!("avx2") {
+        // the cpu supports avx2
+        let done = unsafe { combine_avx2(a, b, &mut out, op) };
+        combine_words(&a[done..], &b[done..], &mut out[done..], op);
+        return out;
+    }
+    combine_words(a, b, &mut out, op);
+    out
+}
+
+// combine_words combines the bitstrings of the same length word by word
+pub(crate) fn combine_words(a: &[u8], b: &[u8], out: &mut [u8], op: BitOp) {
+    let mut o = out.chunks_exact_mut(WORD_BYTES);
+    let mut ca = a.chunks_exact(WORD_BYTES);
+    let mut cb = b.chunks_exact(WORD_BYTES);
+    for ((o, a), b) in o.by_ref().zip(ca.by_ref()).zip(cb.by_ref()) {
+        o.copy_from_slice(&op.apply(word(a), word(b)).to_le_bytes());
+    }
+    let o = o.into_remainder();
+    let w = op
+        .apply(word(ca.remainder()), word(cb.remainder()))
+        .to_le_bytes();
+    let n = o.len();
+    o.copy_from_slice(&w[..n]);
+}
+
+// combine_avx2 combines the bitstrings of the same length 256 bits
+// at a time, returning the number of bytes combined
+#[cfg(all(feature = "std", target_arch = "x86_64"))]
+#[target_feature(enable = "avx2")]
+unsafe fn combine_avx2(a: &[u8], b: &[u8], out: &mut [u8], op: BitOp) -> usize {
+    use core::arch::x86_64::{
+        __m256i, _mm256_and_si256, _mm256_loadu_si256, _mm256_or_si256, _mm256_storeu_si256,
+    };
+    const LANE_BYTES: usize = 32;
+    let lanes = out.len() / LANE_BYTES;
+    for i in 0..lanes {
+        let offset = i * LANE_BYTES;
+        // the loads and the store are unaligned and within the slices
+        let va = _mm256_loadu_si256(a.as_ptr().add(offset) as *const __m256i);
+        let vb = _mm256_loadu_si256(b.as_ptr().add(offset) as *const __m256i);
+        let v = match op {
+            BitOp::Or => _mm256_or_si256(va, vb),
+            BitOp::And => _mm256_and_si256(va, vb),
+        };
+        _mm256_storeu_si256(out.as_mut_ptr().add(offset) as *mut __m256i, v);
+    }
+    lanes * LANE_BYTES
+}
+
+// decode reverses encode, the bitstring cannot exceed the maximum size of the spec
+pub(crate) fn decode(data: &str) -> Result<Vec<u8>, CredentialError> {
+    let bin = decode_config(data, STANDARD).map_err(|e| CredentialError::new(&format!("{}", e)))?;
+    decompress_to_vec_zlib_with_limit(&bin, MAX_BITSTRING_SIZE_KB * 1024).map_err(|e| match e {
+        TINFLStatus::HasMoreOutput => CredentialError::new(&format!(
+            "the bitstring of the encoded list exceeds the maximum size of {}kb",
+            MAX_BITSTRING_SIZE_KB
+        )),
+        e => CredentialError::new(&format!("invalid zlib stream in the encoded list: {:?}", e)),
+    })
+}
+
+// decode_strict is decode for lists that cannot be trusted, the encodedList
+// must be canonical base64 of a single zlib stream, without anything after it,
+// of a bitstring within the size limits. The errors report where the input is malformed
+pub(crate) fn decode_strict(data: &str) -> Result<Vec<u8>, CredentialError> {
+    let invalid = |msg: String| CredentialError::new(&format!("invalid encodedList: {}", msg));
+
+    let bin = decode_config(data, STANDARD).map_err(|e| {
+        invalid(match e {
+            DecodeError::InvalidByte(offset, byte) => format!(
+                "invalid base64 character {:?} at offset {}",
+                byte as char, offset
+            ),
+            DecodeError::InvalidLength => format!("invalid base64 length {}", data.len()),
+            DecodeError::InvalidLastSymbol(offset, byte) => format!(
+                "non-canonical base64 character {:?} at offset {}",
+                byte as char, offset
+            ),
+        })
+    })?;
+    // the canonical base64 is padded and has no bits set after the data
+    let canonical = encode_config(&bin, STANDARD);
+    if canonical != data {
+        let offset = canonical
+            .bytes()
+            .zip(data.bytes())
+            .position(|(a, b)| a != b)
+            .unwrap_or_else(|| canonical.len().min(data.len()));
+        return Err(invalid(format!(
+            "non-canonical base64 at offset {}",
+            offset
+        )));
+    }
+
+    // one more byte than the maximum size to detect larger bitstrings
+    let max = MAX_BITSTRING_SIZE_KB * 1024;
+    let mut bits = vec![0u8; max + 1];
+    let flags = TINFL_FLAG_PARSE_ZLIB_HEADER | TINFL_FLAG_USING_NON_WRAPPING_OUTPUT_BUF;
+    let (status, read, written) = miniz_oxide::inflate::core::decompress(
+        &mut DecompressorOxide::new(),
+        &bin,
+        &mut bits,
+        0,
+        flags,
+    );
+    match status {
+        TINFLStatus::Done if read < bin.len() => {
+            return Err(invalid(format!(
+                "unexpected data after the end of the zlib stream at byte {} of {}",
+                read,
+                bin.len()
+            )))
+        }
+        TINFLStatus::Done if written <= max => (),
+        TINFLStatus::Done | TINFLStatus::HasMoreOutput => {
+            return Err(invalid(format!(
+                "the bitstring exceeds the maximum size of {}kb",
+                MAX_BITSTRING_SIZE_KB
+            )))
+        }
+        TINFLStatus::NeedsMoreInput | TINFLStatus::FailedCannotMakeProgress => {
+            return Err(invalid(format!(
+                "the zlib stream is truncated after {} bytes",
+                read
+            )))
+        }
+        TINFLStatus::Adler32Mismatch => {
+            return Err(invalid(String::from("the zlib checksum doesn't match")))
+        }
+        _ => {
+            return Err(invalid(format!(
+                "malformed zlib stream at byte {} of {}",
+                read,
+                bin.len()
+            )))
+        }
+    }
+    bits.truncate(written);
+    if written < MIN_BITSTRING_SIZE_KN * 1024 {
+        return Err(invalid(format!(
+            "the bitstring is {} bytes, smaller than the minimum size of {}kb",
+            written, MIN_BITSTRING_SIZE_KN
+        )));
+    }
+    Ok(bits)
+}
+
+/// Bitstring is the decoded bitstring of a revocation list, it provides
+/// the status checks on targets without std, such as embedded wallets
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bitstring {
+    bits: Vec<u8>,
+}
+
+impl Bitstring {
+    /// creates an empty bitstring of the given size in kb
+    pub fn new(size: usize) -> Result<Self, CredentialError> {
+        if size < MIN_BITSTRING_SIZE_KN {
+            return Err(CredentialError::new(&format!(
+                "minimum credential size is {}, got {}",
+                MIN_BITSTRING_SIZE_KN, size
+            )));
+        }
+        if size > MAX_BITSTRING_SIZE_KB {
+            return Err(CredentialError::new(&format!(
+                "maximum credential size is {}, got {}",
+                MAX_BITSTRING_SIZE_KB, size
+            )));
+        }
+        Ok(Bitstring {
+            bits: vec![0; size * 1024],
+        })
+    }
+
+    /// decodes the encodedList of a revocation list
+    pub fn decode(encoded_list: &str) -> Result<Self, CredentialError> {
+        decode(encoded_list).map(|bits| Bitstring { bits })
+    }
+
+    /// same as decode, but the encodedList must be canonical base64 of a single
+    /// zlib stream of a bitstring within the size limits of the spec
+    pub fn decode_strict(encoded_list: &str) -> Result<Self, CredentialError> {
+        decode_strict(encoded_list).map(|bits| Bitstring { bits })
+    }
+
+    /// returns the encodedList for the bitstring
+    pub fn encode(&self) -> Result<String, CredentialError> {
+        encode(&self.bits)
+    }
+
+    /// returns the encodedList for the bitstring using the given compression
+    pub fn encode_with(&self, compression: Compression) -> Result<String, CredentialError> {
+        encode_with(&self.bits, compression)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.bits.len() * 8
+    }
+
+    pub fn get(&self, index: u64) -> Result<RevocationStatus, CredentialError> {
+        get(&self.bits, index)
+    }
+
+    pub fn update(&mut self, action: RevocationStatus, index: u64) -> Result<(), CredentialError> {
+        set(&mut self.bits, action, index).map(|_| ())
+    }
+
+    /// returns the number of revoked indexes
+    pub fn revoked_count(&self) -> u64 {
+        count_ones(&self.bits)
+    }
+
+    /// iterates over the revoked indexes in ascending order
+    pub fn revoked_indexes(&self) -> impl Iterator<Item = u64> + '_ {
+        iter_ones(&self.bits)
+    }
+
+    /// returns a bitstring where an index is revoked if it is revoked in either one
+    pub fn union(&self, other: &Bitstring) -> Result<Bitstring, CredentialError> {
+        self.combine(other, BitOp::Or)
+    }
+
+    /// returns a bitstring where an index is revoked only if it is revoked in both
+    pub fn intersection(&self, other: &Bitstring) -> Result<Bitstring, CredentialError> {
+        self.combine(other, BitOp::And)
+    }
+
+    fn combine(&self, other: &Bitstring, op: BitOp) -> Result<Bitstring, CredentialError> {
+        if self.bits.len() != other.bits.len() {
+            return Err(CredentialError::new(&format!(
+                "bitstring sizes don't match, expected {}, got {}",
+                self.bits.len() / 1024,
+                other.bits.len() / 1024
+            )));
+        }
+        Ok(Bitstring {
+            bits: combine(&self.bits, &other.bits, op),
+        })
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bits
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn into_bytes(self) -> Vec<u8> {
+        self.bits
+    }
+}
@@ -0,0 +1,177 @@
+#[cfg(feature = "proof")]
+use crate::DidResolver;
+use crate::{
+    Clock, CredentialError, CredentialStatus, IssuerPolicy, RevocationList2020,
+    RevocationList2020Credential, RevocationStatus, StatusEntry, StatusPurpose, SystemClock,
+    REVOCATION_LIST_2020_STATUS_TYPE,
+};
+use std::collections::HashMap;
+#[cfg(feature = "proof")]
+use std::collections::HashSet;
+
+/// StatusResult is the outcome of the check of a single status entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusResult {
+    pub entry: StatusEntry,
+    /// whether the entry is set in its list, e.g. revoked for a revocation entry
+    pub set: bool,
+}
+
+/// StatusCheck is the combined outcome of the check of all the status entries of a credential
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatusCheck {
+    pub results: Vec<StatusResult>,
+}
+
+impl StatusCheck {
+    fn any(&self, purpose: &StatusPurpose) -> bool {
+        self.results
+            .iter()
+            .any(|r| r.set && &r.entry.purpose == purpose)
+    }
+
+    /// returns true if any revocation entry is set
+    pub fn is_revoked(&self) -> bool {
+        self.any(&StatusPurpose::Revocation)
+    }
+
+    /// returns true if any suspension entry is set
+    pub fn is_suspended(&self) -> bool {
+        self.any(&StatusPurpose::Suspension)
+    }
+
+    /// returns true if no entry is set, whatever its purpose
+    pub fn is_valid(&self) -> bool {
+        self.results.iter().all(|r| !r.set)
+    }
+}
+
+/// StatusChecker evaluates all the status entries of a credential
+/// against the revocation lists they refer to. The issuer of a list
+/// credential is the one it claims, the issuer policy proves nothing
+/// unless the proof of the list credential is verified too, see
+/// add_verified_credential and require_proof
+#[derive(Debug, Clone, Default)]
+pub struct StatusChecker<'a, C: Clock = SystemClock> {
+    clock: C,
+    lists: HashMap<String, &'a RevocationList2020>,
+    credentials: HashMap<String, &'a RevocationList2020Credential>,
+    policy: IssuerPolicy,
+    // the ids of the list credentials added with a verified proof
+    #[cfg(feature = "proof")]
+    verified: HashSet<String>,
+    #[cfg(feature = "proof")]
+    require_proof: bool,
+}
+
+impl<'a> StatusChecker<'a> {
+    pub fn new() -> Self {
+        StatusChecker::default()
+    }
+}
+
+impl<'a, C: Clock> StatusChecker<'a, C> {
+    /// creates a checker that verifies the validity period
+    /// of the revocation list credentials at the time of the clock
+    pub fn with_clock(clock: C) -> Self {
+        StatusChecker {
+            clock,
+            lists: HashMap::new(),
+            credentials: HashMap::new(),
+            policy: IssuerPolicy::default(),
+            #[cfg(feature = "proof")]
+            verified: HashSet::new(),
+            #[cfg(feature = "proof")]
+            require_proof: false,
+        }
+    }
+
+    /// sets the policy of the issuers allowed to publish the status lists
+    /// of a credential, by default the lists must be issued by its issuer
+    pub fn set_issuer_policy(&mut self, policy: IssuerPolicy) {
+        self.policy = policy;
+    }
+
+    /// adds a bare revocation list, status entries refer to it by the list id.
+    /// The list has no issuer nor validity period, so the entries that refer
+    /// to it skip the issuer policy: only add the lists whose origin is trusted
+    /// out of band, and add_credential for the ones published as credentials
+    pub fn add_unbound_list(&mut self, list: &'a RevocationList2020) {
+        self.lists.insert(list.id().to_owned(), list);
+        self.credentials.remove(list.id());
+        #[cfg(feature = "proof")]
+        self.verified.remove(list.id());
+    }
+
+    /// adds the revocation list of a credential, status entries refer to it
+    /// by the credential id. The entries that refer to the credential fail
+    /// the check when it is expired or not yet valid, or when the issuer
+    /// policy doesn't allow its issuer to publish the status of the credential
+    pub fn add_credential(&mut self, credential: &'a RevocationList2020Credential) {
+        self.lists
+            .insert(credential.id().to_owned(), credential.list());
+        self.credentials
+            .insert(credential.id().to_owned(), credential);
+        #[cfg(feature = "proof")]
+        self.verified.remove(credential.id());
+    }
+
+    /// adds the revocation list of a credential as add_credential, after
+    /// verifying its proof with the resolver, see verify_proof
+    #[cfg(feature = "proof")]
+    pub fn add_verified_credential(
+        &mut self,
+        credential: &'a RevocationList2020Credential,
+        resolver: &impl DidResolver,
+    ) -> Result<(), CredentialError> {
+        credential.verify_proof(resolver)?;
+        self.add_credential(credential);
+        self.verified.insert(credential.id().to_owned());
+        Ok(())
+    }
+
+    /// makes the check fail for the entries that refer to a list that
+    /// was not added with add_verified_credential
+    #[cfg(feature = "proof")]
+    pub fn require_proof(&mut self) {
+        self.require_proof = true;
+    }
+
+    /// checks every status entry of the credential, it fails if an entry
+    /// is not supported or it refers to a list that has not been added
+    pub fn check(
+        &self,
+        credential: &impl CredentialStatus,
+    ) -> Result<StatusCheck, CredentialError> {
+        let entries = credential.status_entries();
+        if entries.is_empty() {
+            return Err(CredentialError::new("the credential has no status entries"));
+        }
+        let mut check = StatusCheck::default();
+        for entry in entries {
+            if entry.typ != REVOCATION_LIST_2020_STATUS_TYPE {
+                return Err(CredentialError::new(&format!(
+                    "unsupported credential status type {}",
+                    entry.typ
+                )));
+            }
+            let list = self.lists.get(&entry.list).ok_or_else(|| {
+                CredentialError::new(&format!("no revocation list found for {}", entry.list))
+            })?;
+            #[cfg(feature = "proof")]
+            if self.require_proof && !self.verified.contains(&entry.list) {
+                return Err(CredentialError::new(&format!(
+                    "the proof of the revocation list {} has not been verified",
+                    entry.list
+                )));
+            }
+            if let Some(list_credential) = self.credentials.get(&entry.list) {
+                list_credential.check_validity(self.clock.now())?;
+                list_credential.check_issuer(credential, &self.policy)?;
+            }
+            let set = list.get(entry.index)? == RevocationStatus::Revoke;
+            check.results.push(StatusResult { entry, set });
+        }
+        Ok(check)
+    }
+}
@@ -0,0 +1,478 @@
+use crate::datetime::{format_rfc3339, parse_rfc3339};
+use crate::issuer::issuer_id;
+use crate::{
+    check_contexts, check_status, unix_now, Clock, Compression, CredentialError, CredentialStatus,
+    IssuerPolicy, RevocationList2020, RevocationList2020Status, RevocationStatus, StatusEntry,
+    SystemClock, CREDENTIALS_V1_CONTEXT, CREDENTIALS_V2_CONTEXT, REVOCATION_LIST_2020_CONTEXT,
+    REVOCATION_LIST_2020_STATUS_TYPE,
+};
+#[cfg(feature = "proof")]
+use crate::{jwt, proof, Cryptosuite, DidResolver, Signer, DATA_INTEGRITY_V2_CONTEXT};
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+const REVOCATION_LIST_2020_CREDENTIAL_TYPE: &str = "RevocationList2020Credential";
+
+/// SimpleCredential is a credential that only retains its RevocationList2020Status
+/// entries, the credentialStatus can be a single entry or an array of entries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "RawCredential")]
+pub struct SimpleCredential {
+    #[serde(rename = "credentialStatus")]
+    credential_status: Vec<BasicCredentialStatus>,
+    #[serde(rename = "issuer", skip_serializing_if = "Option::is_none")]
+    issuer: Option<String>,
+}
+
+/// BasicCredentialStatus is the RevocationList2020Status entry of a SimpleCredential
+pub type BasicCredentialStatus = RevocationList2020Status;
+
+#[derive(Deserialize)]
+struct RawCredential {
+    #[serde(rename = "credentialStatus")]
+    credential_status: RawCredentialStatus,
+    #[serde(rename = "issuer", default)]
+    issuer: Option<Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawCredentialStatus {
+    Many(Vec<serde_json::Value>),
+    One(serde_json::Value),
+}
+
+impl TryFrom<RawCredential> for SimpleCredential {
+    type Error = CredentialError;
+
+    fn try_from(raw: RawCredential) -> Result<Self, Self::Error> {
+        let entries = match raw.credential_status {
+            RawCredentialStatus::Many(v) => v,
+            RawCredentialStatus::One(v) => vec![v],
+        };
+        let credential_status = entries
+            .into_iter()
+            .filter(|e| e["type"] == REVOCATION_LIST_2020_STATUS_TYPE)
+            .map(|e| {
+                serde_json::from_value::<BasicCredentialStatus>(e)
+                    .map_err(|e| CredentialError::new(&e.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if credential_status.is_empty() {
+            return Err(CredentialError::new(&format!(
+                "the credential has no {} entry",
+                REVOCATION_LIST_2020_STATUS_TYPE
+            )));
+        }
+        Ok(SimpleCredential {
+            credential_status,
+            issuer: raw.issuer.as_ref().and_then(issuer_id),
+        })
+    }
+}
+
+impl SimpleCredential {
+    /// returns all the RevocationList2020Status entries of the credential
+    pub fn entries(&self) -> &[BasicCredentialStatus] {
+        &self.credential_status
+    }
+
+    /// returns the status entry pointing to the revocation list with the given id
+    pub fn status_for(&self, list_id: &str) -> Option<&BasicCredentialStatus> {
+        self.credential_status
+            .iter()
+            .find(|s| s.revocation_list_credential == list_id)
+    }
+}
+
+/// To be able to use the rl2020 library it's neccessary
+/// to implement the CredentialStatus trait,
+/// the SimpleCredential uses its first status entry
+impl CredentialStatus for SimpleCredential {
+    fn coordinates(&self) -> (String, u64) {
+        self.credential_status[0].coordinates()
+    }
+
+    fn type_def(&self) -> (String, String) {
+        self.credential_status[0].type_def()
+    }
+
+    fn status_entries(&self) -> Vec<StatusEntry> {
+        self.credential_status
+            .iter()
+            .flat_map(|s| s.status_entries())
+            .collect()
+    }
+
+    fn issuer(&self) -> Option<String> {
+        self.issuer.clone()
+    }
+}
+
+/// VcVersion is the version of the VC data model of a credential
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VcVersion {
+    /// VC data model 1.1, the validity is set by issuanceDate and expirationDate
+    V1,
+    /// VC data model 2.0, the validity is set by validFrom and validUntil
+    V2,
+}
+
+impl VcVersion {
+    fn context(&self) -> &'static str {
+        match self {
+            VcVersion::V1 => CREDENTIALS_V1_CONTEXT,
+            VcVersion::V2 => CREDENTIALS_V2_CONTEXT,
+        }
+    }
+
+    // the names of the properties with the start and the end of the validity
+    fn validity_fields(&self) -> (&'static str, &'static str) {
+        match self {
+            VcVersion::V1 => ("issuanceDate", "expirationDate"),
+            VcVersion::V2 => ("validFrom", "validUntil"),
+        }
+    }
+}
+
+/// RevocationList2020Credential is the verifiable credential that wraps a RevocationList2020,
+/// see https://w3c-ccg.github.io/vc-status-rl-2020/#revocationlist2020credential
+/// The fields of the credential not used by the library are preserved as they are
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevocationList2020Credential {
+    #[serde(rename = "id")]
+    id: String,
+    #[serde(rename = "type")]
+    typ: Vec<String>,
+    #[serde(rename = "credentialSubject")]
+    credential_subject: RevocationList2020,
+    #[serde(flatten)]
+    other: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Display for RevocationList2020Credential {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match serde_json::to_string(self) {
+            Ok(s) => write!(f, "{}", s),
+            Err(_) => Err(std::fmt::Error),
+        }
+    }
+}
+
+impl FromStr for RevocationList2020Credential {
+    type Err = CredentialError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_str::<Self>(s)
+            .map_err(|e| CredentialError::new(&e.to_string()))?
+            .decode()
+    }
+}
+
+impl RevocationList2020Credential {
+    /// creates the credential of the revocation list for the given version
+    /// of the VC data model, the credential is valid from now and unsigned
+    pub fn new(
+        id: &str,
+        issuer: &str,
+        list: RevocationList2020,
+        version: VcVersion,
+    ) -> Result<Self, CredentialError> {
+        let mut other = serde_json::Map::new();
+        other.insert(
+            "@context".into(),
+            vec![version.context(), REVOCATION_LIST_2020_CONTEXT].into(),
+        );
+        other.insert("issuer".into(), issuer.into());
+        let mut rc = RevocationList2020Credential {
+            id: id.to_owned(),
+            typ: vec![
+                "VerifiableCredential".to_owned(),
+                REVOCATION_LIST_2020_CREDENTIAL_TYPE.to_owned(),
+            ],
+            credential_subject: list,
+            other,
+        };
+        rc.set_validity(Some(unix_now()), None);
+        rc.check()
+    }
+
+    /// parses a credential that cannot be trusted, the list in the credential
+    /// subject is validated as in RevocationList2020::from_str_strict
+    pub fn from_str_strict(s: &str) -> Result<Self, CredentialError> {
+        let mut rc = serde_json::from_str::<Self>(s)
+            .map_err(|e| CredentialError::new(&e.to_string()))?
+            .check()?;
+        rc.credential_subject = rc.credential_subject.decode_strict()?;
+        Ok(rc)
+    }
+
+    // decode validates a deserialized credential and decodes its revocation list
+    pub(crate) fn decode(self) -> Result<Self, CredentialError> {
+        let mut rc = self.check()?;
+        rc.credential_subject = rc.credential_subject.decode()?;
+        Ok(rc)
+    }
+
+    // check validates the fields of the credential
+    fn check(self) -> Result<Self, CredentialError> {
+        let rc = self;
+        if rc.id.trim().is_empty() {
+            return Err(CredentialError::new(
+                "revocation list credential id cannot be empty",
+            ));
+        }
+        if !rc
+            .typ
+            .iter()
+            .any(|t| t == REVOCATION_LIST_2020_CREDENTIAL_TYPE)
+        {
+            return Err(CredentialError::new(&format!(
+                "the credential type must include {}",
+                REVOCATION_LIST_2020_CREDENTIAL_TYPE
+            )));
+        }
+        Ok(rc)
+    }
+
+    /// checks that the credential includes the JSON-LD contexts of
+    /// the types it uses, see jsonld::check_contexts
+    pub fn check_contexts(&self) -> Result<(), CredentialError> {
+        let doc = serde_json::to_value(self).map_err(|e| CredentialError::new(&e.to_string()))?;
+        check_contexts(&doc)
+    }
+
+    /// returns the version of the VC data model of the credential, from its first context
+    pub fn version(&self) -> VcVersion {
+        let context = self.other.get("@context");
+        match context
+            .map(|c| c.get(0).unwrap_or(c))
+            .and_then(|c| c.as_str())
+        {
+            Some(CREDENTIALS_V2_CONTEXT) => VcVersion::V2,
+            _ => VcVersion::V1,
+        }
+    }
+
+    /// converts the credential to the given version of the VC data model,
+    /// replacing its base context and renaming the validity properties.
+    /// The proof of the credential, if any, is not valid anymore
+    pub fn set_version(&mut self, version: VcVersion) {
+        let (from, until) = self.version().validity_fields();
+        let (from, until) = (self.other.remove(from), self.other.remove(until));
+        let mut contexts = match self.other.remove("@context") {
+            Some(Value::Array(v)) => v,
+            Some(c) => vec![c],
+            None => vec![REVOCATION_LIST_2020_CONTEXT.into()],
+        };
+        contexts.retain(|c| c != CREDENTIALS_V1_CONTEXT && c != CREDENTIALS_V2_CONTEXT);
+        contexts.insert(0, version.context().into());
+        self.other.insert("@context".into(), contexts.into());
+        let fields = version.validity_fields();
+        for (field, date) in [(fields.0, from), (fields.1, until)] {
+            if let Some(date) = date {
+                self.other.insert(field.into(), date);
+            }
+        }
+    }
+
+    /// sets the validity period of the credential as unix timestamps (in seconds),
+    /// using the properties of its version of the VC data model
+    pub fn set_validity(&mut self, from: Option<u64>, until: Option<u64>) {
+        let fields = self.version().validity_fields();
+        for (field, date) in [(fields.0, from), (fields.1, until)] {
+            match date {
+                Some(ts) => self.other.insert(field.into(), format_rfc3339(ts).into()),
+                None => self.other.remove(field),
+            };
+        }
+    }
+
+    // date returns the unix timestamp of a date property of the credential
+    fn date(&self, field: &str) -> Result<Option<u64>, CredentialError> {
+        match self.other.get(field) {
+            None => Ok(None),
+            Some(Value::String(s)) => parse_rfc3339(s).map(Some),
+            Some(_) => Err(CredentialError::new(&format!(
+                "the {} of the credential must be a date",
+                field
+            ))),
+        }
+    }
+
+    /// returns the unix timestamp since when the credential is valid, from
+    /// the validFrom (VC 2.0) or issuanceDate (VC 1.1) property
+    pub fn valid_from(&self) -> Result<Option<u64>, CredentialError> {
+        self.date(self.version().validity_fields().0)
+    }
+
+    /// returns the unix timestamp when the credential expires, from
+    /// the validUntil (VC 2.0) or expirationDate (VC 1.1) property
+    pub fn valid_until(&self) -> Result<Option<u64>, CredentialError> {
+        self.date(self.version().validity_fields().1)
+    }
+
+    /// checks that the credential is valid at the unix timestamp now
+    pub fn check_validity(&self, now: u64) -> Result<(), CredentialError> {
+        if let Some(from) = self.valid_from()?.filter(|from| *from > now) {
+            return Err(CredentialError::new(&format!(
+                "the revocation list credential {} is not valid before {}",
+                self.id,
+                format_rfc3339(from)
+            )));
+        }
+        if let Some(until) = self.valid_until()?.filter(|until| *until < now) {
+            return Err(CredentialError::new(&format!(
+                "the revocation list credential {} expired at {}",
+                self.id,
+                format_rfc3339(until)
+            )));
+        }
+        Ok(())
+    }
+
+    /// returns the id of the credential, that is the value
+    /// credentials refer to in the revocationListCredential field
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// returns the id of the issuer of the credential
+    pub fn issuer(&self) -> Option<String> {
+        self.other.get("issuer").and_then(issuer_id)
+    }
+
+    /// checks that the policy allows the issuer of the credential to publish
+    /// the status of the checked credential. A credential that doesn't tell
+    /// its issuer, like a bare status entry, fails the check unless the policy
+    /// allows unknown issuers. The issuer of the list is the one it claims,
+    /// the check proves nothing unless the proof of the list is verified too
+    pub fn check_issuer(
+        &self,
+        credential: &impl CredentialStatus,
+        policy: &IssuerPolicy,
+    ) -> Result<(), CredentialError> {
+        policy.check(credential.issuer().as_deref(), self.issuer().as_deref())
+    }
+
+    /// signs the credential with a Data Integrity proof of the cryptosuite, the key
+    /// id of the signer must be an assertionMethod of the issuer. The proof
+    /// covers the current content of the list, it must be signed again after
+    /// an update of the list. A VC 1.1 credential gets the Data Integrity
+    /// context, that defines the terms of the proof
+    #[cfg(feature = "proof")]
+    pub fn sign(
+        &mut self,
+        signer: &impl Signer,
+        cryptosuite: Cryptosuite,
+    ) -> Result<(), CredentialError> {
+        self.other.remove("proof");
+        if self.version() == VcVersion::V1 {
+            let mut contexts = match self.other.remove("@context") {
+                Some(Value::Array(v)) => v,
+                Some(c) => vec![c],
+                None => vec![],
+            };
+            if !contexts.iter().any(|c| c == DATA_INTEGRITY_V2_CONTEXT) {
+                contexts.push(DATA_INTEGRITY_V2_CONTEXT.into());
+            }
+            self.other.insert("@context".into(), contexts.into());
+        }
+        self.credential_subject.encode_for_publication()?;
+        let document =
+            serde_json::to_value(&*self).map_err(|e| CredentialError::new(&e.to_string()))?;
+        let proof = proof::create_proof(&document, signer, cryptosuite, unix_now())?;
+        self.other.insert("proof".into(), proof);
+        Ok(())
+    }
+
+    /// verifies the proof of the credential, resolving its verification method
+    /// with the resolver, the method must be an assertionMethod of the issuer
+    #[cfg(feature = "proof")]
+    pub fn verify_proof(&self, resolver: &impl DidResolver) -> Result<(), CredentialError> {
+        let issuer = self
+            .issuer()
+            .ok_or_else(|| CredentialError::new("the revocation list credential has no issuer"))?;
+        let document =
+            serde_json::to_value(self).map_err(|e| CredentialError::new(&e.to_string()))?;
+        proof::verify_proof(&document, &issuer, resolver)
+    }
+
+    /// encodes the credential as a JWT signed by the signer, as the
+    /// vc claim for VC 1.1 or as the claims set for VC 2.0
+    #[cfg(feature = "proof")]
+    pub fn to_jwt(&self, signer: &impl Signer) -> Result<String, CredentialError> {
+        let mut credential = self.clone();
+        credential.credential_subject.encode_for_publication()?;
+        let document =
+            serde_json::to_value(&credential).map_err(|e| CredentialError::new(&e.to_string()))?;
+        jwt::encode(&document, self.version(), signer)
+    }
+
+    /// decodes a JWT encoded credential, verifying its signature with the key of
+    /// the kid, that must be an assertionMethod of the issuer of the credential
+    #[cfg(feature = "proof")]
+    pub fn from_jwt(jwt: &str, resolver: &impl DidResolver) -> Result<Self, CredentialError> {
+        Self::from_str(&jwt::decode(jwt, resolver)?.to_string())
+    }
+
+    /// returns the revocation list in the credential subject
+    pub fn list(&self) -> &RevocationList2020 {
+        &self.credential_subject
+    }
+
+    /// returns the JSON of the credential canonicalized according to RFC 8785 (JCS)
+    pub fn to_canonical_json(&self) -> Result<String, CredentialError> {
+        serde_jcs::to_string(self).map_err(|e| CredentialError::new(&e.to_string()))
+    }
+
+    /// sets the compression of the encodedList of the list in the credential subject,
+    /// see RevocationList2020::set_compression. The lists of the credentials are
+    /// encoded for publication when they are signed, with sign or to_jwt
+    pub fn set_compression(&mut self, compression: Compression) -> Result<(), CredentialError> {
+        self.credential_subject.set_compression(compression)
+    }
+
+    pub fn revoke(&mut self, credential: &impl CredentialStatus) -> Result<(), CredentialError> {
+        check_status(credential, &self.id)
+            .and_then(|i| self.credential_subject.update(RevocationStatus::Revoke, i))
+    }
+
+    pub fn reset(&mut self, credential: &impl CredentialStatus) -> Result<(), CredentialError> {
+        check_status(credential, &self.id)
+            .and_then(|i| self.credential_subject.update(RevocationStatus::Reset, i))
+    }
+
+    /// returns true if the credential is revoked, the list must be valid at the
+    /// current time and issued by the issuer of the credential, see check_issuer
+    pub fn is_revoked(&self, credential: &impl CredentialStatus) -> Result<bool, CredentialError> {
+        self.is_revoked_with_policy(credential, &IssuerPolicy::default())
+    }
+
+    /// same as is_revoked, with the policy deciding who can issue the list
+    pub fn is_revoked_with_policy(
+        &self,
+        credential: &impl CredentialStatus,
+        policy: &IssuerPolicy,
+    ) -> Result<bool, CredentialError> {
+        self.is_revoked_at(credential, policy, &SystemClock)
+    }
+
+    /// same as is_revoked_with_policy, with the validity of the list checked
+    /// at the time of the clock
+    pub fn is_revoked_at(
+        &self,
+        credential: &impl CredentialStatus,
+        policy: &IssuerPolicy,
+        clock: &impl Clock,
+    ) -> Result<bool, CredentialError> {
+        self.check_validity(clock.now())?;
+        self.check_issuer(credential, policy)?;
+        check_status(credential, &self.id)
+            .and_then(|i| self.credential_subject.get(i))
+            .map(|s| s == RevocationStatus::Revoke)
+    }
+}
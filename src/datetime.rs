@@ -0,0 +1,113 @@
+use crate::CredentialError;
+
+// Conversions between unix timestamps (in seconds) and the RFC 3339 dates of
+// the credentials (xsd:dateTime), the calendar conversions are the ones of
+// http://howardhinnant.github.io/date_algorithms.html
+
+// days_from_civil returns the days since 1970-01-01 of a date of the gregorian calendar
+fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((m + 9) % 12) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+// civil_from_days returns the date of the gregorian calendar of the days since 1970-01-01
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400;
+    (if m <= 2 { y + 1 } else { y }, m, d)
+}
+
+fn days_in_month(y: i64, m: i64) -> i64 {
+    match m {
+        2 if y % 4 == 0 && (y % 100 != 0 || y % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// formats a unix timestamp as an RFC 3339 date in UTC, e.g. 2020-04-05T14:27:42Z
+pub(crate) fn format_rfc3339(ts: u64) -> String {
+    let (days, secs) = ((ts / 86400) as i64, ts % 86400);
+    let (y, m, d) = civil_from_days(days);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        y,
+        m,
+        d,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+/// parses an RFC 3339 date to a unix timestamp, the fractions of second are truncated
+pub(crate) fn parse_rfc3339(s: &str) -> Result<u64, CredentialError> {
+    let invalid = || {
+        CredentialError::new(&format!(
+            "invalid date {:?}, expected an RFC 3339 date-time",
+            s
+        ))
+    };
+    let b = s.as_bytes();
+    let num = |from: usize, to: usize| -> Result<i64, CredentialError> {
+        match b.get(from..to) {
+            Some(digits) if digits.iter().all(u8::is_ascii_digit) => {
+                Ok(digits.iter().fold(0, |n, d| n * 10 + (d - b'0') as i64))
+            }
+            _ => Err(invalid()),
+        }
+    };
+    let sep = |i: usize, c: &[u8]| b.get(i).filter(|x| c.contains(x)).ok_or_else(invalid);
+    let (y, m, d) = (num(0, 4)?, num(5, 7)?, num(8, 10)?);
+    let (hh, mm, ss) = (num(11, 13)?, num(14, 16)?, num(17, 19)?);
+    sep(4, b"-")?;
+    sep(7, b"-")?;
+    sep(10, b"Tt ")?;
+    sep(13, b":")?;
+    sep(16, b":")?;
+    if !(1..=12).contains(&m) || d < 1 || d > days_in_month(y, m) || hh > 23 || mm > 59 || ss > 60 {
+        return Err(invalid());
+    }
+    // skip the fraction of second
+    let mut i = 19;
+    if b.get(i) == Some(&b'.') {
+        i += 1;
+        let digits = b[i..].iter().take_while(|c| c.is_ascii_digit()).count();
+        if digits == 0 {
+            return Err(invalid());
+        }
+        i += digits;
+    }
+    let offset = match b.get(i) {
+        Some(b'Z') | Some(b'z') if b.len() == i + 1 => 0,
+        Some(sign @ (b'+' | b'-')) if b.len() == i + 6 => {
+            sep(i + 3, b":")?;
+            let (oh, om) = (num(i + 1, i + 3)?, num(i + 4, i + 6)?);
+            if oh > 23 || om > 59 {
+                return Err(invalid());
+            }
+            let offset = oh * 3600 + om * 60;
+            if *sign == b'+' {
+                offset
+            } else {
+                -offset
+            }
+        }
+        _ => return Err(invalid()),
+    };
+    // a leap second is counted as the last second of the minute
+    let ts = days_from_civil(y, m, d) * 86400 + hh * 3600 + mm * 60 + ss.min(59) - offset;
+    u64::try_from(ts).map_err(|_| invalid())
+}
@@ -0,0 +1,133 @@
+use alloc::vec::Vec;
+
+// A minimal zlib encoder whose output only depends on its input, unlike the
+// output of the zlib libraries that can change from a version to another.
+// The bitstring is encoded in a single deflate block with the fixed huffman
+// codes of RFC 1951, runs of at least 3 equal bytes are encoded as matches
+// at distance 1 and everything else as literals. It is effective on sparse
+// bitstrings, that are mostly made of runs of zeros
+
+// the zlib header for deflate with a 32k window, its check bits make it a multiple of 31
+const ZLIB_HEADER: [u8; 2] = [0x78, 0x01];
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const END_OF_BLOCK: u16 = 256;
+
+// the base lengths and extra bits of the length symbols 257 to 285
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+// BitWriter packs bits starting from the least significant bit of each byte
+struct BitWriter {
+    out: Vec<u8>,
+    acc: u32,
+    n: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, bits: u8) {
+        self.acc |= value << self.n;
+        self.n += bits;
+        while self.n >= 8 {
+            self.out.push(self.acc as u8);
+            self.acc >>= 8;
+            self.n -= 8;
+        }
+    }
+
+    // huffman codes are packed starting from their most significant bit
+    fn write_code(&mut self, code: u32, bits: u8) {
+        self.write(code.reverse_bits() >> (32 - bits), bits);
+    }
+
+    // write_symbol writes a literal/length symbol with the fixed huffman codes
+    fn write_symbol(&mut self, symbol: u16) {
+        let s = symbol as u32;
+        match symbol {
+            0..=143 => self.write_code(0x30 + s, 8),
+            144..=255 => self.write_code(0x190 + s - 144, 9),
+            256..=279 => self.write_code(s - 256, 7),
+            _ => self.write_code(0xc0 + s - 280, 8),
+        }
+    }
+
+    fn write_match(&mut self, length: usize) {
+        let code = match length {
+            MAX_MATCH => LENGTH_BASE.len() - 1,
+            _ => LENGTH_BASE
+                .iter()
+                .rposition(|b| *b as usize <= length)
+                .unwrap(),
+        };
+        self.write_symbol(257 + code as u16);
+        let extra = LENGTH_EXTRA[code];
+        if extra > 0 {
+            self.write((length - LENGTH_BASE[code] as usize) as u32, extra);
+        }
+        // the distance 1 has the 5 bits code 0 and no extra bits
+        self.write_code(0, 5);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.n > 0 {
+            self.out.push(self.acc as u8);
+        }
+        self.out
+    }
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 is the largest chunk that cannot overflow before the modulo
+    for chunk in data.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+// compress_zlib returns the zlib stream of the data, that is always the same for the same data
+pub(crate) fn compress_zlib(data: &[u8]) -> Vec<u8> {
+    let mut w = BitWriter {
+        out: Vec::from(ZLIB_HEADER),
+        acc: 0,
+        n: 0,
+    };
+    // final block with the fixed huffman codes
+    w.write(1, 1);
+    w.write(1, 2);
+
+    let mut i = 0;
+    while i < data.len() {
+        let run = match i {
+            0 => 0,
+            _ => data[i..]
+                .iter()
+                .take(MAX_MATCH)
+                .take_while(|b| **b == data[i - 1])
+                .count(),
+        };
+        if run >= MIN_MATCH {
+            w.write_match(run);
+            i += run;
+        } else {
+            w.write_symbol(data[i] as u16);
+            i += 1;
+        }
+    }
+    w.write_symbol(END_OF_BLOCK);
+
+    let mut out = w.finish();
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
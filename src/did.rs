@@ -0,0 +1,337 @@
+use crate::CredentialError;
+use base64::URL_SAFE_NO_PAD;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+// Resolution of the keys of the issuers from their DIDs, to verify the proofs
+// of the credentials. did:key is resolved locally, did:web fetches the DID
+// document through a Fetcher so that the library never opens connections itself
+
+// the multicodec prefixes of the public keys, as unsigned varints
+const ED25519_PUB: [u8; 2] = [0xed, 0x01];
+const P256_PUB: [u8; 2] = [0x80, 0x24];
+const P384_PUB: [u8; 2] = [0x81, 0x24];
+
+const DID_CONTEXT: &str = "https://www.w3.org/ns/did/v1";
+const MULTIKEY_CONTEXT: &str = "https://w3id.org/security/multikey/v1";
+
+/// PublicKey is the public key of a verification method
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PublicKey {
+    Ed25519([u8; 32]),
+    /// a P-256 key as a compressed SEC1 point
+    P256(Vec<u8>),
+    /// a P-384 key as a compressed SEC1 point
+    P384(Vec<u8>),
+}
+
+fn invalid_key(e: impl std::fmt::Display) -> CredentialError {
+    CredentialError::new(&format!("invalid public key: {}", e))
+}
+
+impl PublicKey {
+    fn ed25519(bytes: &[u8]) -> Result<Self, CredentialError> {
+        let key: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| invalid_key("an Ed25519 key must be 32 bytes long"))?;
+        ed25519_dalek::VerifyingKey::from_bytes(&key).map_err(invalid_key)?;
+        Ok(PublicKey::Ed25519(key))
+    }
+
+    fn p256(sec1: &[u8]) -> Result<Self, CredentialError> {
+        let key = p256::PublicKey::from_sec1_bytes(sec1).map_err(invalid_key)?;
+        Ok(PublicKey::P256(
+            p256::EncodedPoint::from(key).compress().as_bytes().to_vec(),
+        ))
+    }
+
+    fn p384(sec1: &[u8]) -> Result<Self, CredentialError> {
+        let key = p384::PublicKey::from_sec1_bytes(sec1).map_err(invalid_key)?;
+        Ok(PublicKey::P384(
+            p384::EncodedPoint::from(key).compress().as_bytes().to_vec(),
+        ))
+    }
+
+    /// decodes a base58btc multibase multicodec key, that is the
+    /// publicKeyMultibase of a verification method and the suffix of did:key
+    pub fn from_multibase(s: &str) -> Result<Self, CredentialError> {
+        let encoded = s
+            .strip_prefix('z')
+            .ok_or_else(|| invalid_key("only base58btc multibase keys are supported"))?;
+        let bytes = bs58::decode(encoded).into_vec().map_err(invalid_key)?;
+        match bytes.split_at_checked(2) {
+            Some((codec, key)) if codec == ED25519_PUB => Self::ed25519(key),
+            Some((codec, key)) if codec == P256_PUB => Self::p256(key),
+            Some((codec, key)) if codec == P384_PUB => Self::p384(key),
+            _ => Err(invalid_key("unsupported multicodec key type")),
+        }
+    }
+
+    /// returns the base58btc multibase multicodec encoding of the key
+    pub fn to_multibase(&self) -> String {
+        let (codec, key) = match self {
+            PublicKey::Ed25519(key) => (ED25519_PUB, key.as_slice()),
+            PublicKey::P256(key) => (P256_PUB, key.as_slice()),
+            PublicKey::P384(key) => (P384_PUB, key.as_slice()),
+        };
+        format!(
+            "z{}",
+            bs58::encode([&codec[..], key].concat()).into_string()
+        )
+    }
+
+    fn from_jwk(jwk: &Value) -> Result<Self, CredentialError> {
+        let coordinate = |name: &str| {
+            jwk[name]
+                .as_str()
+                .ok_or_else(|| invalid_key(format!("the JWK has no {}", name)))
+                .and_then(|c| base64::decode_config(c, URL_SAFE_NO_PAD).map_err(invalid_key))
+        };
+        match (jwk["kty"].as_str(), jwk["crv"].as_str()) {
+            (Some("OKP"), Some("Ed25519")) => Self::ed25519(&coordinate("x")?),
+            (Some("EC"), Some("P-256")) => {
+                Self::p256(&[vec![0x04], coordinate("x")?, coordinate("y")?].concat())
+            }
+            (Some("EC"), Some("P-384")) => {
+                Self::p384(&[vec![0x04], coordinate("x")?, coordinate("y")?].concat())
+            }
+            (kty, crv) => Err(invalid_key(format!(
+                "unsupported JWK key type {} {}",
+                kty.unwrap_or_default(),
+                crv.unwrap_or_default()
+            ))),
+        }
+    }
+
+    // from_method reads the key of a verification method of a DID document
+    fn from_method(method: &Value) -> Result<Self, CredentialError> {
+        if let Some(multibase) = method["publicKeyMultibase"].as_str() {
+            return Self::from_multibase(multibase);
+        }
+        if let Some(jwk) = method.get("publicKeyJwk") {
+            return Self::from_jwk(jwk);
+        }
+        match (method["type"].as_str(), method["publicKeyBase58"].as_str()) {
+            (Some("Ed25519VerificationKey2018"), Some(key)) => {
+                Self::ed25519(&bs58::decode(key).into_vec().map_err(invalid_key)?)
+            }
+            _ => Err(invalid_key("the verification method has no supported key")),
+        }
+    }
+}
+
+/// VerificationMethod is a key of a DID, resolved from its DID document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationMethod {
+    pub id: String,
+    /// the DID that controls the key
+    pub controller: String,
+    pub key: PublicKey,
+}
+
+/// DidResolver resolves a DID to its DID document
+pub trait DidResolver {
+    fn resolve(&self, did: &str) -> Result<Value, CredentialError>;
+}
+
+impl<R: DidResolver + ?Sized> DidResolver for &R {
+    fn resolve(&self, did: &str) -> Result<Value, CredentialError> {
+        (*self).resolve(did)
+    }
+}
+
+/// DidKeyResolver resolves the did:key DIDs of Ed25519, P-256 and P-384 keys,
+/// see https://w3c-ccg.github.io/did-method-key/
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DidKeyResolver;
+
+impl DidKeyResolver {
+    /// returns the did:key of the public key
+    pub fn did(key: &PublicKey) -> String {
+        format!("did:key:{}", key.to_multibase())
+    }
+}
+
+impl DidResolver for DidKeyResolver {
+    fn resolve(&self, did: &str) -> Result<Value, CredentialError> {
+        let multibase = did
+            .strip_prefix("did:key:")
+            .ok_or_else(|| CredentialError::new(&format!("{} is not a did:key", did)))?;
+        PublicKey::from_multibase(multibase)?;
+        let id = format!("{}#{}", did, multibase);
+        Ok(json!({
+            "@context": [DID_CONTEXT, MULTIKEY_CONTEXT],
+            "id": did,
+            "verificationMethod": [{
+                "id": id,
+                "type": "Multikey",
+                "controller": did,
+                "publicKeyMultibase": multibase
+            }],
+            "authentication": [id],
+            "assertionMethod": [id],
+            "capabilityInvocation": [id],
+            "capabilityDelegation": [id]
+        }))
+    }
+}
+
+/// Fetcher retrieves the document published at a URL
+pub trait Fetcher {
+    fn fetch(&self, url: &str) -> Result<Vec<u8>, CredentialError>;
+}
+
+/// DirFetcher serves the https URLs from a local directory, the document of
+/// https://example.com/user/did.json is read from <root>/example.com/user/did.json
+#[derive(Debug, Clone)]
+pub struct DirFetcher {
+    root: PathBuf,
+}
+
+impl DirFetcher {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        DirFetcher { root: root.into() }
+    }
+}
+
+impl Fetcher for DirFetcher {
+    fn fetch(&self, url: &str) -> Result<Vec<u8>, CredentialError> {
+        let path = url
+            .strip_prefix("https://")
+            .filter(|p| p.split('/').all(|s| !s.is_empty() && s != "." && s != ".."))
+            .ok_or_else(|| CredentialError::new(&format!("cannot fetch {}", url)))?;
+        fs::read(self.root.join(path))
+            .map_err(|e| CredentialError::new(&format!("error fetching {}: {}", url, e)))
+    }
+}
+
+/// DidWebResolver resolves did:web DIDs fetching their DID document,
+/// see https://w3c-ccg.github.io/did-method-web/
+#[derive(Debug, Clone)]
+pub struct DidWebResolver<F: Fetcher> {
+    fetcher: F,
+}
+
+impl<F: Fetcher> DidWebResolver<F> {
+    pub fn new(fetcher: F) -> Self {
+        DidWebResolver { fetcher }
+    }
+}
+
+// did_web_url returns the URL of the DID document of a did:web
+fn did_web_url(did: &str) -> Result<String, CredentialError> {
+    let invalid = || CredentialError::new(&format!("{} is not a valid did:web", did));
+    let mut segments = did.strip_prefix("did:web:").ok_or_else(invalid)?.split(':');
+    // the port of the domain is percent encoded
+    let domain = segments.next().unwrap_or_default().replace("%3A", ":");
+    let path: Vec<&str> = segments.collect();
+    if domain.is_empty() || domain.contains('/') || path.iter().any(|s| s.is_empty()) {
+        return Err(invalid());
+    }
+    Ok(match path.is_empty() {
+        true => format!("https://{}/.well-known/did.json", domain),
+        false => format!("https://{}/{}/did.json", domain, path.join("/")),
+    })
+}
+
+impl<F: Fetcher> DidResolver for DidWebResolver<F> {
+    fn resolve(&self, did: &str) -> Result<Value, CredentialError> {
+        let url = did_web_url(did)?;
+        let document = self.fetcher.fetch(&url)?;
+        serde_json::from_slice(&document)
+            .map_err(|e| CredentialError::new(&format!("invalid DID document at {}: {}", url, e)))
+    }
+}
+
+/// DidResolvers resolves the DIDs with the resolver of their method,
+/// by default only did:key is supported
+pub struct DidResolvers {
+    methods: HashMap<String, Box<dyn DidResolver>>,
+}
+
+impl Default for DidResolvers {
+    fn default() -> Self {
+        let mut resolvers = DidResolvers {
+            methods: HashMap::new(),
+        };
+        resolvers.add("key", DidKeyResolver);
+        resolvers
+    }
+}
+
+impl DidResolvers {
+    pub fn new() -> Self {
+        DidResolvers::default()
+    }
+
+    /// sets the resolver of a DID method, e.g. web for did:web
+    pub fn add(&mut self, method: &str, resolver: impl DidResolver + 'static) {
+        self.methods.insert(method.to_owned(), Box::new(resolver));
+    }
+}
+
+impl DidResolver for DidResolvers {
+    fn resolve(&self, did: &str) -> Result<Value, CredentialError> {
+        let method = did
+            .strip_prefix("did:")
+            .and_then(|d| d.split(':').next())
+            .unwrap_or_default();
+        self.methods
+            .get(method)
+            .ok_or_else(|| CredentialError::new(&format!("unsupported DID method of {}", did)))?
+            .resolve(did)
+    }
+}
+
+// same_id compares an id of a DID document, that can be relative to the DID, with an absolute id
+fn same_id(did: &str, id: &str, absolute: &str) -> bool {
+    id == absolute || (id.starts_with('#') && format!("{}{}", did, id) == absolute)
+}
+
+/// resolves the verification method, that must be listed in the verification
+/// relationship of the DID document of its controller, e.g. assertionMethod
+pub fn resolve_verification_method(
+    resolver: &impl DidResolver,
+    id: &str,
+    relationship: &str,
+) -> Result<VerificationMethod, CredentialError> {
+    let did = id.split('#').next().unwrap_or_default();
+    let document = resolver.resolve(did)?;
+    if document["id"] != did {
+        return Err(CredentialError::new(&format!(
+            "the DID document of {} has a different id",
+            did
+        )));
+    }
+    let methods = |name: &str| document[name].as_array().cloned().unwrap_or_default();
+    // the relationship lists references to the methods or the methods themselves
+    let method = methods(relationship)
+        .into_iter()
+        .find_map(|m| match &m {
+            Value::String(r) if same_id(did, r, id) => methods("verificationMethod")
+                .into_iter()
+                .find(|vm| same_id(did, vm["id"].as_str().unwrap_or_default(), id)),
+            Value::Object(_) if same_id(did, m["id"].as_str().unwrap_or_default(), id) => Some(m),
+            _ => None,
+        })
+        .ok_or_else(|| {
+            CredentialError::new(&format!(
+                "the verification method {} is not in the {} of {}",
+                id, relationship, did
+            ))
+        })?;
+    let controller = method["controller"].as_str().unwrap_or(did);
+    if controller != did {
+        return Err(CredentialError::new(&format!(
+            "the verification method {} is controlled by {}",
+            id, controller
+        )));
+    }
+    Ok(VerificationMethod {
+        id: id.to_owned(),
+        controller: controller.to_owned(),
+        key: PublicKey::from_method(&method)?,
+    })
+}
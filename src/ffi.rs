@@ -0,0 +1,232 @@
+use crate::{CredentialError, RevocationList2020, RevocationStatus};
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::str::FromStr;
+
+// The C ABI of the library, the header is generated in include/rl2020.h
+// when building with the ffi feature. A list is handed out as an opaque
+// pointer that must be released with rl2020_list_free, all the functions
+// return a result code and on failure the message of the error can be
+// retrieved with rl2020_last_error
+
+/// Rl2020Result is the result code returned by the functions of the C ABI
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rl2020Result {
+    Ok = 0,
+    /// a required pointer argument is null
+    NullPointer = 1,
+    /// a string argument is not valid utf-8
+    InvalidString = 2,
+    /// the operation on the list failed, e.g. an invalid list or an index out of range
+    Failed = 3,
+    /// the library panicked, the state of the list is unspecified
+    Panic = 4,
+}
+
+type FfiError = (Rl2020Result, String);
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+impl From<CredentialError> for FfiError {
+    fn from(e: CredentialError) -> Self {
+        (Rl2020Result::Failed, e.to_string())
+    }
+}
+
+// call runs f catching panics, so that they do not unwind across the ffi boundary,
+// and stores the message of the error for rl2020_last_error
+fn call<F: FnOnce() -> Result<(), FfiError>>(f: F) -> Rl2020Result {
+    let result = panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|_| {
+        Err((
+            Rl2020Result::Panic,
+            String::from("unexpected panic in rl2020"),
+        ))
+    });
+    match result {
+        Ok(()) => Rl2020Result::Ok,
+        Err((code, message)) => {
+            // the message is plain text, replace nul bytes to not lose it
+            let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+            LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+            code
+        }
+    }
+}
+
+fn null_pointer(name: &str) -> FfiError {
+    (
+        Rl2020Result::NullPointer,
+        format!("the {} pointer is null", name),
+    )
+}
+
+unsafe fn to_str<'a>(s: *const c_char, name: &str) -> Result<&'a str, FfiError> {
+    if s.is_null() {
+        return Err(null_pointer(name));
+    }
+    CStr::from_ptr(s).to_str().map_err(|e| {
+        (
+            Rl2020Result::InvalidString,
+            format!("the {} is not valid utf-8: {}", name, e),
+        )
+    })
+}
+
+unsafe fn to_list<'a>(list: *const RevocationList2020) -> Result<&'a RevocationList2020, FfiError> {
+    list.as_ref().ok_or_else(|| null_pointer("list"))
+}
+
+unsafe fn to_list_mut<'a>(
+    list: *mut RevocationList2020,
+) -> Result<&'a mut RevocationList2020, FfiError> {
+    list.as_mut().ok_or_else(|| null_pointer("list"))
+}
+
+unsafe fn write_list(out: *mut *mut RevocationList2020, rl: RevocationList2020) {
+    *out = Box::into_raw(Box::new(rl));
+}
+
+/// Returns the message of the last error that occurred on the calling thread,
+/// or null if no error occurred. The message is owned by the library and is
+/// valid until the next failing call on the same thread
+#[no_mangle]
+pub extern "C" fn rl2020_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |m| m.as_ptr()))
+}
+
+/// Creates an empty revocation list with the given id and size in kb
+///
+/// # Safety
+/// `id` must be a nul terminated string and `out` a valid pointer,
+/// the list written to `out` must be released with `rl2020_list_free`
+#[no_mangle]
+pub unsafe extern "C" fn rl2020_list_new(
+    id: *const c_char,
+    size: usize,
+    out: *mut *mut RevocationList2020,
+) -> Rl2020Result {
+    call(|| {
+        if out.is_null() {
+            return Err(null_pointer("out"));
+        }
+        let rl = RevocationList2020::new(to_str(id, "id")?, size)?;
+        write_list(out, rl);
+        Ok(())
+    })
+}
+
+/// Parses a revocation list from its JSON representation
+///
+/// # Safety
+/// `json` must be a nul terminated string and `out` a valid pointer,
+/// the list written to `out` must be released with `rl2020_list_free`
+#[no_mangle]
+pub unsafe extern "C" fn rl2020_list_parse(
+    json: *const c_char,
+    out: *mut *mut RevocationList2020,
+) -> Rl2020Result {
+    call(|| {
+        if out.is_null() {
+            return Err(null_pointer("out"));
+        }
+        let rl = RevocationList2020::from_str(to_str(json, "json")?)?;
+        write_list(out, rl);
+        Ok(())
+    })
+}
+
+/// Revokes the credential at the given index
+///
+/// # Safety
+/// `list` must be a list returned by `rl2020_list_new` or `rl2020_list_parse`
+#[no_mangle]
+pub unsafe extern "C" fn rl2020_list_revoke(
+    list: *mut RevocationList2020,
+    index: u64,
+) -> Rl2020Result {
+    call(|| Ok(to_list_mut(list)?.update(RevocationStatus::Revoke, index)?))
+}
+
+/// Resets (un-revokes) the credential at the given index
+///
+/// # Safety
+/// `list` must be a list returned by `rl2020_list_new` or `rl2020_list_parse`
+#[no_mangle]
+pub unsafe extern "C" fn rl2020_list_reset(
+    list: *mut RevocationList2020,
+    index: u64,
+) -> Rl2020Result {
+    call(|| Ok(to_list_mut(list)?.update(RevocationStatus::Reset, index)?))
+}
+
+/// Writes to `out` whether the credential at the given index is revoked
+///
+/// # Safety
+/// `list` must be a list returned by `rl2020_list_new` or `rl2020_list_parse`
+/// and `out` a valid pointer
+#[no_mangle]
+pub unsafe extern "C" fn rl2020_list_is_revoked(
+    list: *const RevocationList2020,
+    index: u64,
+    out: *mut bool,
+) -> Rl2020Result {
+    call(|| {
+        if out.is_null() {
+            return Err(null_pointer("out"));
+        }
+        *out = to_list(list)?.get(index)? == RevocationStatus::Revoke;
+        Ok(())
+    })
+}
+
+/// Writes to `out` the JSON representation of the list
+///
+/// # Safety
+/// `list` must be a list returned by `rl2020_list_new` or `rl2020_list_parse`
+/// and `out` a valid pointer, the string written to `out` must be released
+/// with `rl2020_string_free`
+#[no_mangle]
+pub unsafe extern "C" fn rl2020_list_serialize(
+    list: *const RevocationList2020,
+    out: *mut *mut c_char,
+) -> Rl2020Result {
+    call(|| {
+        if out.is_null() {
+            return Err(null_pointer("out"));
+        }
+        let json = to_list(list)?.to_string();
+        let json = CString::new(json).map_err(|e| (Rl2020Result::Failed, e.to_string()))?;
+        *out = json.into_raw();
+        Ok(())
+    })
+}
+
+/// Releases a list, passing null is a no-op
+///
+/// # Safety
+/// `list` must be a list returned by `rl2020_list_new` or `rl2020_list_parse`
+/// that has not been released already
+#[no_mangle]
+pub unsafe extern "C" fn rl2020_list_free(list: *mut RevocationList2020) {
+    if !list.is_null() {
+        drop(Box::from_raw(list));
+    }
+}
+
+/// Releases a string returned by the library, passing null is a no-op
+///
+/// # Safety
+/// `s` must be a string returned by `rl2020_list_serialize`
+/// that has not been released already
+#[no_mangle]
+pub unsafe extern "C" fn rl2020_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
@@ -0,0 +1,77 @@
+use crate::CredentialError;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+// issuer_id returns the id of the issuer of a credential,
+// the issuer is either its id or an object with an id
+pub(crate) fn issuer_id(issuer: &Value) -> Option<String> {
+    match issuer {
+        Value::String(id) => Some(id.to_owned()),
+        Value::Object(m) => m.get("id").and_then(|id| id.as_str()).map(String::from),
+        _ => None,
+    }
+}
+
+/// IssuerPolicy decides which issuers can publish the status lists of the
+/// credentials of an issuer. By default a status list must be issued by the
+/// same issuer of the credential, otherwise anyone could publish a list
+/// claiming that nothing is revoked, and a credential without an issuer
+/// cannot be checked
+#[derive(Debug, Clone, Default)]
+pub struct IssuerPolicy {
+    delegates: HashMap<String, HashSet<String>>,
+    allow_unknown_issuer: bool,
+}
+
+impl IssuerPolicy {
+    pub fn new() -> Self {
+        IssuerPolicy::default()
+    }
+
+    /// allows the status issuer to publish the status lists
+    /// of the credentials issued by the issuer
+    pub fn allow_delegate(&mut self, issuer: &str, status_issuer: &str) {
+        self.delegates
+            .entry(issuer.to_owned())
+            .or_default()
+            .insert(status_issuer.to_owned());
+    }
+
+    /// allows checking the credentials that don't tell their issuer, like bare
+    /// status entries, against any status list. The caller must know that the
+    /// list is published by the issuer of the credential
+    pub fn allow_unknown_issuer(&mut self) {
+        self.allow_unknown_issuer = true;
+    }
+
+    /// checks that the status list issued by list_issuer can publish the
+    /// status of a credential issued by issuer, that is None if unknown
+    pub fn check(
+        &self,
+        issuer: Option<&str>,
+        list_issuer: Option<&str>,
+    ) -> Result<(), CredentialError> {
+        let Some(issuer) = issuer else {
+            if self.allow_unknown_issuer {
+                return Ok(());
+            }
+            return Err(CredentialError::new(
+                "the credential has no issuer, the issuer of the status list cannot be checked",
+            ));
+        };
+        let list_issuer = list_issuer
+            .ok_or_else(|| CredentialError::new("the status list credential has no issuer"))?;
+        if issuer == list_issuer
+            || self
+                .delegates
+                .get(issuer)
+                .is_some_and(|d| d.contains(list_issuer))
+        {
+            return Ok(());
+        }
+        Err(CredentialError::new(&format!(
+            "the status list is issued by {}, that cannot publish the status of the credentials of {}",
+            list_issuer, issuer
+        )))
+    }
+}
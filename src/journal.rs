@@ -0,0 +1,118 @@
+use crate::{unix_now, CredentialError, RevocationList2020, RevocationStatus};
+use serde_derive::{Deserialize, Serialize};
+
+/// JournalEntry records a single status change of a revocation list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    #[serde(rename = "index")]
+    pub index: u64,
+    #[serde(rename = "oldStatus")]
+    pub old_status: RevocationStatus,
+    #[serde(rename = "newStatus")]
+    pub new_status: RevocationStatus,
+    /// unix timestamp (in seconds) of the change
+    #[serde(rename = "timestamp")]
+    pub timestamp: u64,
+    #[serde(rename = "actor")]
+    pub actor: String,
+    #[serde(rename = "reason")]
+    pub reason: String,
+    /// version of the list after the change, the changes
+    /// of a single mutation share the same version
+    #[serde(rename = "version")]
+    pub version: u64,
+}
+
+impl JournalEntry {
+    /// creates a new entry timestamped with the current time
+    pub fn new(
+        index: u64,
+        old_status: RevocationStatus,
+        new_status: RevocationStatus,
+        actor: &str,
+        reason: &str,
+    ) -> Self {
+        JournalEntry {
+            index,
+            old_status,
+            new_status,
+            timestamp: unix_now(),
+            actor: String::from(actor),
+            reason: String::from(reason),
+            version: 0,
+        }
+    }
+}
+
+/// Journal is an append-only log of the status changes of a revocation list
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Journal {
+    entries: Vec<JournalEntry>,
+}
+
+impl Journal {
+    pub(crate) fn push(&mut self, entry: JournalEntry) {
+        self.entries.push(entry);
+    }
+
+    pub fn entries(&self) -> &[JournalEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// exports the journal in the JSON Lines format, one entry per line
+    pub fn to_jsonl(&self) -> Result<String, CredentialError> {
+        let mut out = String::new();
+        for entry in &self.entries {
+            let line =
+                serde_json::to_string(entry).map_err(|e| CredentialError::new(&e.to_string()))?;
+            out.push_str(&line);
+            out.push('\n');
+        }
+        Ok(out)
+    }
+
+    /// parses a journal exported with to_jsonl, empty lines are ignored
+    pub fn from_jsonl(data: &str) -> Result<Self, CredentialError> {
+        let mut journal = Journal::default();
+        for (n, line) in data.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry = serde_json::from_str::<JournalEntry>(line).map_err(|e| {
+                CredentialError::new(&format!("invalid journal entry at line {}: {}", n + 1, e))
+            })?;
+            journal.push(entry);
+        }
+        Ok(journal)
+    }
+
+    /// rebuilds a revocation list by applying all the entries of the journal,
+    /// in order, to an empty list. The returned list has the journal enabled
+    /// and the version of the last entry, it fails if an entry doesn't match
+    /// the status of the list being rebuilt
+    pub fn replay(&self, id: &str, size: usize) -> Result<RevocationList2020, CredentialError> {
+        let mut rl = RevocationList2020::new(id, size)?;
+        rl.enable_journal();
+        for (n, entry) in self.entries.iter().enumerate() {
+            let old = rl.set(entry.new_status, entry.index)?;
+            rl.version = entry.version;
+            if old != entry.old_status {
+                return Err(CredentialError::new(&format!(
+                    "journal entry {} expects index {} to be {:?}, found {:?}",
+                    n, entry.index, entry.old_status, old,
+                )));
+            }
+            rl.record(entry.clone());
+        }
+        rl.encoded_list = RevocationList2020::pack(&rl.bit_set, rl.compression)?;
+        Ok(rl)
+    }
+}
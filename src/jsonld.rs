@@ -0,0 +1,735 @@
+use crate::{CredentialError, CredentialStatus, StatusEntry, StatusPurpose};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+// JSON-LD support for the credentials handled by the library. The contexts
+// of the specs are bundled in contexts/ so that documents can be checked and
+// expanded offline. The expansion is a subset of the JSON-LD 1.1 algorithm
+// that covers the constructs used by the bundled contexts: term and prefix
+// definitions, @vocab, keyword aliases, property and type scoped contexts,
+// type coercion, protected terms and @graph containers. Base IRIs are not
+// processed and terms that do not expand to an IRI are dropped, the strict
+// expansion used to sign the documents rejects them instead, together with
+// all the constructs that the bundled contexts don't use
+
+pub const CREDENTIALS_V1_CONTEXT: &str = "https://www.w3.org/2018/credentials/v1";
+pub const CREDENTIALS_V2_CONTEXT: &str = "https://www.w3.org/ns/credentials/v2";
+pub const REVOCATION_LIST_2020_CONTEXT: &str = "https://w3id.org/vc-revocation-list-2020/v1";
+pub const STATUS_LIST_2021_CONTEXT: &str = "https://w3id.org/vc/status-list/2021/v1";
+pub const DATA_INTEGRITY_V2_CONTEXT: &str = "https://w3id.org/security/data-integrity/v2";
+
+const BUNDLED_CONTEXTS: [(&str, &str); 5] = [
+    (
+        CREDENTIALS_V1_CONTEXT,
+        include_str!("../contexts/credentials-v1.jsonld"),
+    ),
+    (
+        CREDENTIALS_V2_CONTEXT,
+        include_str!("../contexts/credentials-v2.jsonld"),
+    ),
+    (
+        REVOCATION_LIST_2020_CONTEXT,
+        include_str!("../contexts/vc-revocation-list-2020-v1.jsonld"),
+    ),
+    (
+        STATUS_LIST_2021_CONTEXT,
+        include_str!("../contexts/vc-status-list-2021-v1.jsonld"),
+    ),
+    (
+        DATA_INTEGRITY_V2_CONTEXT,
+        include_str!("../contexts/data-integrity-v2.jsonld"),
+    ),
+];
+
+// the contexts required by the types of the specs
+const TYPE_CONTEXTS: [(&str, &str); 9] = [
+    ("RevocationList2020Credential", REVOCATION_LIST_2020_CONTEXT),
+    ("RevocationList2020", REVOCATION_LIST_2020_CONTEXT),
+    ("RevocationList2020Status", REVOCATION_LIST_2020_CONTEXT),
+    ("StatusList2021Credential", STATUS_LIST_2021_CONTEXT),
+    ("StatusList2021", STATUS_LIST_2021_CONTEXT),
+    ("StatusList2021Entry", STATUS_LIST_2021_CONTEXT),
+    ("BitstringStatusListCredential", CREDENTIALS_V2_CONTEXT),
+    ("BitstringStatusList", CREDENTIALS_V2_CONTEXT),
+    ("BitstringStatusListEntry", CREDENTIALS_V2_CONTEXT),
+];
+
+const CREDENTIAL_STATUS_IRI: &str = "https://www.w3.org/2018/credentials#credentialStatus";
+const ISSUER_IRI: &str = "https://www.w3.org/2018/credentials#issuer";
+const RL2020_NS: &str = "https://w3id.org/vc-revocation-list-2020#";
+const SL2021_NS: &str = "https://w3id.org/vc/status-list#";
+const BSL_NS: &str = "https://www.w3.org/ns/credentials/status#";
+
+// the status entry types by their expanded IRI, with the namespace and
+// the names of their list and index properties
+const STATUS_ENTRY_TYPES: [(&str, &str, &str, &str); 3] = [
+    (
+        "RevocationList2020Status",
+        RL2020_NS,
+        "revocationListCredential",
+        "revocationListIndex",
+    ),
+    (
+        "StatusList2021Entry",
+        SL2021_NS,
+        "statusListCredential",
+        "statusListIndex",
+    ),
+    (
+        "BitstringStatusListEntry",
+        BSL_NS,
+        "statusListCredential",
+        "statusListIndex",
+    ),
+];
+
+// remote contexts that load other contexts are followed up to this depth
+const MAX_CONTEXT_DEPTH: usize = 8;
+
+// the keywords of the constructs of the bundled contexts, the only ones
+// accepted by the strict expansion in the contexts, in the term definitions,
+// as containers and in the documents
+const CONTEXT_KEYWORDS: [&str; 3] = ["@protected", "@version", "@vocab"];
+const TERM_KEYWORDS: [&str; 5] = ["@container", "@context", "@id", "@protected", "@type"];
+const CONTAINERS: [&str; 3] = ["@graph", "@list", "@set"];
+const NODE_KEYWORDS: [&str; 4] = ["@context", "@graph", "@id", "@type"];
+
+/// DocumentLoader resolves the URLs of the remote contexts of a document
+pub trait DocumentLoader {
+    /// returns the JSON document published at the url
+    fn load(&self, url: &str) -> Result<Value, CredentialError>;
+}
+
+/// StaticLoader is a DocumentLoader that never goes on the network,
+/// by default it knows the contexts bundled with the library
+#[derive(Debug, Clone)]
+pub struct StaticLoader {
+    documents: HashMap<String, Value>,
+}
+
+impl Default for StaticLoader {
+    fn default() -> Self {
+        let documents = BUNDLED_CONTEXTS
+            .iter()
+            .map(|(url, doc)| {
+                let doc = serde_json::from_str(doc).expect("bundled contexts are valid JSON");
+                (url.to_string(), doc)
+            })
+            .collect();
+        StaticLoader { documents }
+    }
+}
+
+impl StaticLoader {
+    /// adds or replaces the document published at the url
+    pub fn insert(&mut self, url: &str, document: Value) {
+        self.documents.insert(url.to_string(), document);
+    }
+}
+
+impl DocumentLoader for StaticLoader {
+    fn load(&self, url: &str) -> Result<Value, CredentialError> {
+        self.documents
+            .get(url)
+            .cloned()
+            .ok_or_else(|| CredentialError::new(&format!("unknown JSON-LD context {}", url)))
+    }
+}
+
+// context_urls returns the remote contexts of the document, in order
+fn context_urls(doc: &Value) -> Result<Vec<&str>, CredentialError> {
+    match doc.get("@context") {
+        Some(Value::String(s)) => Ok(vec![s.as_str()]),
+        Some(Value::Array(v)) => Ok(v.iter().filter_map(|c| c.as_str()).collect()),
+        Some(Value::Object(_)) => Ok(vec![]),
+        _ => Err(CredentialError::new("the document has no @context")),
+    }
+}
+
+// collect_types appends to types the type of all the objects in the value
+fn collect_types<'a>(value: &'a Value, types: &mut Vec<&'a str>) {
+    match value {
+        Value::Array(v) => v.iter().for_each(|x| collect_types(x, types)),
+        Value::Object(m) => {
+            match m.get("type") {
+                Some(Value::String(t)) => types.push(t),
+                Some(Value::Array(v)) => types.extend(v.iter().filter_map(|t| t.as_str())),
+                _ => {}
+            }
+            m.iter()
+                .filter(|(k, _)| *k != "@context")
+                .for_each(|(_, x)| collect_types(x, types));
+        }
+        _ => {}
+    }
+}
+
+/// checks that the document starts with the VC v1 or v2 context
+/// and includes the contexts that define the status types it uses
+pub fn check_contexts(doc: &Value) -> Result<(), CredentialError> {
+    let urls = context_urls(doc)?;
+    match urls.first() {
+        Some(&CREDENTIALS_V1_CONTEXT) | Some(&CREDENTIALS_V2_CONTEXT) => {}
+        _ => {
+            return Err(CredentialError::new(&format!(
+                "the first @context must be {} or {}",
+                CREDENTIALS_V1_CONTEXT, CREDENTIALS_V2_CONTEXT
+            )))
+        }
+    }
+    let mut types = Vec::new();
+    collect_types(doc, &mut types);
+    for t in types {
+        if let Some((_, url)) = TYPE_CONTEXTS.iter().find(|(name, _)| *name == t) {
+            if !urls.contains(url) {
+                return Err(CredentialError::new(&format!(
+                    "the type {} requires the {} context",
+                    t, url
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Term {
+    id: String,
+    typ: Option<String>,
+    container: Option<String>,
+    context: Option<Value>,
+    protected: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Context {
+    terms: HashMap<String, Term>,
+    vocab: Option<String>,
+}
+
+impl Context {
+    // expand_iri expands a term, a compact IRI or, relative to the vocabulary, a name
+    fn expand_iri(&self, value: &str, vocab: bool) -> Option<String> {
+        if value.starts_with('@') {
+            return Some(value.to_string());
+        }
+        if let Some(term) = self.terms.get(value).filter(|_| vocab) {
+            return Some(term.id.clone());
+        }
+        if let Some((prefix, suffix)) = value.split_once(':') {
+            if suffix.starts_with("//") {
+                return Some(value.to_string());
+            }
+            return match self.terms.get(prefix) {
+                Some(term) => Some(format!("{}{}", term.id, suffix)),
+                None => Some(value.to_string()),
+            };
+        }
+        match &self.vocab {
+            Some(v) if vocab => Some(format!("{}{}", v, value)),
+            // relative IRIs are kept as they are
+            _ if !vocab => Some(value.to_string()),
+            _ => None,
+        }
+    }
+
+    // process returns the context updated with the local context, the protected
+    // terms can only be redefined by the contexts scoped to a property
+    fn process(
+        &self,
+        local: &Value,
+        loader: &impl DocumentLoader,
+        strict: bool,
+        override_protected: bool,
+        depth: usize,
+    ) -> Result<Context, CredentialError> {
+        if depth > MAX_CONTEXT_DEPTH {
+            return Err(CredentialError::new("too many nested JSON-LD contexts"));
+        }
+        match local {
+            Value::Null if !override_protected && self.terms.values().any(|t| t.protected) => Err(
+                CredentialError::new("invalid JSON-LD context nullification of protected terms"),
+            ),
+            Value::Null => Ok(Context::default()),
+            Value::String(url) => {
+                let doc = loader.load(url)?;
+                self.process(
+                    &doc["@context"],
+                    loader,
+                    strict,
+                    override_protected,
+                    depth + 1,
+                )
+            }
+            Value::Array(v) => v.iter().try_fold(self.clone(), |ctx, c| {
+                ctx.process(c, loader, strict, override_protected, depth)
+            }),
+            Value::Object(m) => {
+                if let Some(key) = m.keys().find(|k| {
+                    strict && k.starts_with('@') && !CONTEXT_KEYWORDS.contains(&k.as_str())
+                }) {
+                    return Err(CredentialError::new(&format!(
+                        "the JSON-LD context keyword {} is not supported",
+                        key
+                    )));
+                }
+                let mut ctx = self.clone();
+                if let Some(vocab) = m.get("@vocab") {
+                    ctx.vocab = vocab.as_str().and_then(|v| ctx.expand_iri(v, true));
+                    if strict && !ctx.vocab.as_deref().is_some_and(|v| v.contains(':')) {
+                        return Err(CredentialError::new(&format!(
+                            "the JSON-LD @vocab {} is not an absolute IRI",
+                            vocab
+                        )));
+                    }
+                }
+                let protected = m.get("@protected") == Some(&Value::Bool(true));
+                for (key, def) in m.iter().filter(|(k, _)| !k.starts_with('@')) {
+                    let term = ctx.define(m, key, def, protected, strict)?;
+                    if let Some(previous) = ctx.terms.get(key).filter(|t| t.protected) {
+                        // a protected term can be redefined in the same way only
+                        let same = term.as_ref().is_some_and(|t| {
+                            Term {
+                                protected: true,
+                                ..t.clone()
+                            } == *previous
+                        });
+                        match (same, override_protected) {
+                            (true, _) => continue,
+                            (false, false) => {
+                                return Err(CredentialError::new(&format!(
+                                    "the protected JSON-LD term {} cannot be redefined",
+                                    key
+                                )))
+                            }
+                            (false, true) => {}
+                        }
+                    }
+                    match term {
+                        Some(term) => ctx.terms.insert(key.clone(), term),
+                        None => ctx.terms.remove(key),
+                    };
+                }
+                Ok(ctx)
+            }
+            _ => Err(CredentialError::new("invalid JSON-LD @context")),
+        }
+    }
+
+    // define creates the definition of a term of the local context
+    fn define(
+        &self,
+        local: &Map<String, Value>,
+        key: &str,
+        def: &Value,
+        protected: bool,
+        strict: bool,
+    ) -> Result<Option<Term>, CredentialError> {
+        // a prefix can be defined after the terms that use it
+        let expand = |iri: &str| match iri.split_once(':') {
+            Some((prefix, suffix)) if prefix != key && !suffix.starts_with("//") => {
+                match local.get(prefix) {
+                    Some(Value::String(p)) => Some(format!("{}{}", p, suffix)),
+                    _ => self.expand_iri(iri, true),
+                }
+            }
+            _ => self.expand_iri(iri, true),
+        };
+        let (id, def) = match def {
+            Value::Null => return Ok(None),
+            Value::String(id) => (Some(id.as_str()), &Map::new()),
+            Value::Object(def) => (def.get("@id").and_then(|i| i.as_str()), def),
+            _ => {
+                return Err(CredentialError::new(&format!(
+                    "invalid JSON-LD term definition for {}",
+                    key
+                )))
+            }
+        };
+        if strict {
+            let unsupported = def
+                .keys()
+                .map(String::as_str)
+                .find(|k| !TERM_KEYWORDS.contains(k))
+                .or_else(|| {
+                    def.get("@container")
+                        .filter(|c| !c.as_str().is_some_and(|c| CONTAINERS.contains(&c)))
+                        .map(|_| "@container")
+                });
+            if let Some(keyword) = unsupported {
+                return Err(CredentialError::new(&format!(
+                    "the {} of the JSON-LD term {} is not supported",
+                    keyword, key
+                )));
+            }
+        }
+        let id = match id {
+            Some(id) => expand(id),
+            None => expand(key),
+        };
+        Ok(id.map(|id| Term {
+            id,
+            typ: def.get("@type").and_then(|t| t.as_str()).and_then(expand),
+            container: def
+                .get("@container")
+                .and_then(|c| c.as_str())
+                .map(String::from),
+            context: def.get("@context").cloned(),
+            protected: match def.get("@protected") {
+                Some(p) => p == &Value::Bool(true),
+                None => protected,
+            },
+        }))
+    }
+}
+
+fn as_strings(value: &Value) -> Vec<&str> {
+    match value {
+        Value::String(s) => vec![s],
+        Value::Array(v) => v.iter().filter_map(|x| x.as_str()).collect(),
+        _ => vec![],
+    }
+}
+
+// expand_value expands a scalar according to the coercion of its term
+fn expand_value(ctx: &Context, term: Option<&Term>, value: &Value) -> Value {
+    let mut out = Map::new();
+    match (term.and_then(|t| t.typ.as_deref()), value) {
+        (Some("@id"), Value::String(s)) => {
+            out.insert("@id".into(), ctx.expand_iri(s, false).into());
+        }
+        (Some("@vocab"), Value::String(s)) => {
+            out.insert("@id".into(), ctx.expand_iri(s, true).into());
+        }
+        (Some(t), _) if t != "@id" && t != "@vocab" && t != "@none" => {
+            out.insert("@type".into(), t.into());
+            out.insert("@value".into(), value.clone());
+        }
+        _ => {
+            out.insert("@value".into(), value.clone());
+        }
+    }
+    Value::Object(out)
+}
+
+// Expander expands the documents, in strict mode the terms that do not
+// expand to an IRI are an error instead of being dropped, as required to
+// sign a document since the dropped terms would not be covered by the proof
+struct Expander<'a, L: DocumentLoader> {
+    loader: &'a L,
+    strict: bool,
+}
+
+impl<L: DocumentLoader> Expander<'_, L> {
+    fn undefined(&self, term: &str) -> Result<(), CredentialError> {
+        match self.strict {
+            true => Err(CredentialError::new(&format!(
+                "the term {} is not defined by the JSON-LD @context",
+                term
+            ))),
+            false => Ok(()),
+        }
+    }
+
+    // context processes a local context, property scoped
+    // contexts can redefine the protected terms
+    fn context(
+        &self,
+        ctx: &Context,
+        local: &Value,
+        property_scoped: bool,
+    ) -> Result<Context, CredentialError> {
+        ctx.process(local, self.loader, self.strict, property_scoped, 0)
+    }
+
+    // relative fails in strict mode on the relative IRIs,
+    // since the base IRI is not processed
+    fn relative(&self, iri: Option<&str>) -> Result<(), CredentialError> {
+        match iri {
+            Some(id) if self.strict && !id.contains(':') => Err(CredentialError::new(&format!(
+                "the IRI {} is relative, the JSON-LD base IRI is not supported",
+                id
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    // element expands the value of a property, nested nodes are expanded
+    // with the context that the node propagates and the scoped context of the term
+    fn element(
+        &self,
+        outer: &Context,
+        ctx: &Context,
+        term: Option<&Term>,
+        element: &Value,
+    ) -> Result<Vec<Value>, CredentialError> {
+        let scoped = term.and_then(|t| t.context.as_ref());
+        match element {
+            Value::Null => Ok(vec![]),
+            Value::Array(v) => v.iter().try_fold(Vec::new(), |mut acc, x| {
+                acc.extend(self.element(outer, ctx, term, x)?);
+                Ok(acc)
+            }),
+            Value::Object(m) => self.node(outer, scoped, m).map(|n| n.into_iter().collect()),
+            // the scoped context of the term applies to the @vocab coercion of the value
+            _ => {
+                let value = match scoped {
+                    Some(c) => expand_value(&self.context(ctx, c, true)?, term, element),
+                    None => expand_value(ctx, term, element),
+                };
+                self.relative(value.get("@id").and_then(|id| id.as_str()))?;
+                Ok(vec![value])
+            }
+        }
+    }
+
+    fn node(
+        &self,
+        outer: &Context,
+        scoped: Option<&Value>,
+        node: &Map<String, Value>,
+    ) -> Result<Option<Value>, CredentialError> {
+        let mut active = match scoped {
+            Some(c) => self.context(outer, c, true)?,
+            None => outer.clone(),
+        };
+        if let Some(local) = node.get("@context") {
+            active = self.context(&active, local, false)?;
+        }
+        // the type scoped contexts only apply to the properties of the node
+        let mut types: Vec<&str> = node
+            .iter()
+            .filter(|(k, _)| active.expand_iri(k, true).as_deref() == Some("@type"))
+            .flat_map(|(_, v)| as_strings(v))
+            .collect();
+        types.sort_unstable();
+        let mut typed = active.clone();
+        for t in &types {
+            if let Some(c) = active.terms.get(*t).and_then(|t| t.context.as_ref()) {
+                typed = self.context(&typed, c, false)?;
+            }
+        }
+
+        let mut out = Map::new();
+        for (key, value) in node.iter().filter(|(k, _)| *k != "@context") {
+            let property = match typed.expand_iri(key, true) {
+                Some(p) if p.starts_with('@') || p.contains(':') => p,
+                _ => {
+                    self.undefined(key)?;
+                    continue;
+                }
+            };
+            if self.strict && property.starts_with('@') && !NODE_KEYWORDS.contains(&&*property) {
+                return Err(CredentialError::new(&format!(
+                    "the JSON-LD keyword {} is not supported",
+                    property
+                )));
+            }
+            let expanded = match property.as_str() {
+                "@id" => match value.as_str() {
+                    Some(id) => {
+                        let id = typed.expand_iri(id, false);
+                        self.relative(id.as_deref())?;
+                        id.into()
+                    }
+                    None => continue,
+                },
+                "@type" => {
+                    let mut iris = Vec::new();
+                    for t in &types {
+                        match active.expand_iri(t, true).filter(|t| t.contains(':')) {
+                            Some(iri) => iris.push(iri),
+                            None => self.undefined(t)?,
+                        }
+                    }
+                    iris.into()
+                }
+                "@graph" => self.element(&active, &active, None, value)?.into(),
+                p if p.starts_with('@') => value.clone(),
+                _ => {
+                    let term = typed.terms.get(key.as_str());
+                    if term.and_then(|t| t.typ.as_deref()) == Some("@json") {
+                        let mut json = Map::new();
+                        json.insert("@type".into(), "@json".into());
+                        json.insert("@value".into(), value.clone());
+                        vec![Value::Object(json)]
+                    } else {
+                        let values = self.element(&active, &typed, term, value)?;
+                        match term.and_then(|t| t.container.as_deref()) {
+                            Some("@graph") => values
+                                .into_iter()
+                                .map(|v| {
+                                    let mut graph = Map::new();
+                                    graph.insert("@graph".into(), vec![v].into());
+                                    Value::Object(graph)
+                                })
+                                .collect(),
+                            Some("@list") => {
+                                let mut list = Map::new();
+                                list.insert("@list".into(), values.into());
+                                vec![Value::Object(list)]
+                            }
+                            _ => values,
+                        }
+                    }
+                    .into()
+                }
+            };
+            match (out.get_mut(&property), expanded) {
+                (Some(Value::Array(existing)), Value::Array(more)) => existing.extend(more),
+                (_, expanded) => {
+                    out.insert(property, expanded);
+                }
+            }
+        }
+        Ok(match out.is_empty() {
+            true => None,
+            false => Some(Value::Object(out)),
+        })
+    }
+
+    fn expand(&self, doc: &Value) -> Result<Value, CredentialError> {
+        let ctx = Context::default();
+        let mut expanded = self.element(&ctx, &ctx, None, doc)?;
+        // a top level object with only a @graph is replaced by its content
+        if let [Value::Object(m)] = expanded.as_slice() {
+            if m.len() == 1 {
+                if let Some(Value::Array(graph)) = m.get("@graph") {
+                    expanded = graph.clone();
+                }
+            }
+        }
+        Ok(Value::Array(expanded))
+    }
+}
+
+/// expands a JSON-LD document resolving its contexts with the loader,
+/// see https://www.w3.org/TR/json-ld11-api/#expansion-algorithms
+pub fn expand(doc: &Value, loader: &impl DocumentLoader) -> Result<Value, CredentialError> {
+    Expander {
+        loader,
+        strict: false,
+    }
+    .expand(doc)
+}
+
+// expand_strict expands a document failing on the terms that are not defined
+#[cfg(feature = "proof")]
+pub(crate) fn expand_strict(
+    doc: &Value,
+    loader: &impl DocumentLoader,
+) -> Result<Value, CredentialError> {
+    Expander {
+        loader,
+        strict: true,
+    }
+    .expand(doc)
+}
+
+// first_value returns the first @id or @value of an expanded property
+fn first_value<'a>(node: &'a Value, property: &str) -> Option<&'a Value> {
+    let value = node.get(property)?.get(0)?;
+    value.get("@id").or_else(|| value.get("@value"))
+}
+
+// parse_expanded_entry parses an expanded status entry according to its type IRI,
+// the entries of unknown types are skipped
+fn parse_expanded_entry(entry: &Value) -> Result<Option<StatusEntry>, CredentialError> {
+    let types = as_strings(&entry["@type"]);
+    let (typ, ns, list, index) = match STATUS_ENTRY_TYPES
+        .iter()
+        .find(|(name, ns, _, _)| types.iter().any(|t| *t == format!("{}{}", ns, name)))
+    {
+        Some(t) => t,
+        None => return Ok(None),
+    };
+    let missing = |p: &str| CredentialError::new(&format!("the {} entry has no {}", typ, p));
+    let list = first_value(entry, &format!("{}{}", ns, list))
+        .and_then(|l| l.as_str())
+        .ok_or_else(|| missing(list))?;
+    let index = match first_value(entry, &format!("{}{}", ns, index)) {
+        Some(Value::Number(n)) => n.as_u64(),
+        Some(Value::String(s)) => s.parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| missing(index))?;
+    let purpose = first_value(entry, &format!("{}statusPurpose", ns))
+        .and_then(|p| p.as_str())
+        .map(StatusPurpose::from)
+        .unwrap_or(StatusPurpose::Revocation);
+    Ok(Some(StatusEntry {
+        id: entry["@id"].as_str().unwrap_or_default().to_string(),
+        typ: typ.to_string(),
+        purpose,
+        list: list.to_string(),
+        index,
+    }))
+}
+
+/// ExpandedCredential is a verifiable credential whose status entries
+/// are identified by the IRIs of the JSON-LD expansion, so that the
+/// terms of the credential are the ones defined by its contexts
+#[derive(Debug, Clone)]
+pub struct ExpandedCredential {
+    entries: Vec<StatusEntry>,
+    issuer: Option<String>,
+}
+
+impl ExpandedCredential {
+    /// expands the credential with the loader and parses its status entries,
+    /// skipping the ones of unsupported types as find_status_entries does
+    pub fn new(vc: &Value, loader: &impl DocumentLoader) -> Result<Self, CredentialError> {
+        let expanded = expand(vc, loader)?;
+        let status = expanded[0]
+            .get(CREDENTIAL_STATUS_IRI)
+            .and_then(|s| s.as_array())
+            .ok_or_else(|| CredentialError::new("the credential has no credentialStatus"))?;
+        let entries: Vec<StatusEntry> = status
+            .iter()
+            .map(parse_expanded_entry)
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .flatten()
+            .collect();
+        if entries.is_empty() {
+            return Err(CredentialError::new(
+                "the credential has no supported credentialStatus",
+            ));
+        }
+        let issuer = first_value(&expanded[0], ISSUER_IRI)
+            .and_then(|i| i.as_str())
+            .map(String::from);
+        Ok(ExpandedCredential { entries, issuer })
+    }
+
+    /// returns the status entries of the credential
+    pub fn entries(&self) -> &[StatusEntry] {
+        &self.entries
+    }
+
+    // primary returns the RevocationList2020Status entry, or the first one
+    fn primary(&self) -> &StatusEntry {
+        self.entries
+            .iter()
+            .find(|e| e.typ == STATUS_ENTRY_TYPES[0].0)
+            .unwrap_or(&self.entries[0])
+    }
+}
+
+impl CredentialStatus for ExpandedCredential {
+    fn coordinates(&self) -> (String, u64) {
+        self.primary().coordinates()
+    }
+
+    fn type_def(&self) -> (String, String) {
+        self.primary().type_def()
+    }
+
+    fn status_entries(&self) -> Vec<StatusEntry> {
+        self.entries.clone()
+    }
+
+    fn issuer(&self) -> Option<String> {
+        self.issuer.clone()
+    }
+}
@@ -0,0 +1,144 @@
+use crate::datetime::{format_rfc3339, parse_rfc3339};
+use crate::did::{resolve_verification_method, DidResolver};
+use crate::issuer::issuer_id;
+use crate::proof::verify_signature;
+use crate::{Algorithm, CredentialError, Signer, VcVersion};
+use base64::URL_SAFE_NO_PAD;
+use serde_json::{json, Value};
+
+// JWT encodings of the credentials, signed with EdDSA, ES256 or ES384. A VC 1.1
+// credential is the vc claim of the JWT, see
+// https://www.w3.org/TR/vc-data-model/#json-web-token, a VC 2.0 credential is
+// the claims set itself, see https://www.w3.org/TR/vc-jose-cose/
+
+const JWT_TYPE: &str = "JWT";
+const VC_JWT_TYPE: &str = "vc+jwt";
+const ASSERTION_METHOD: &str = "assertionMethod";
+
+fn invalid_jwt(e: impl std::fmt::Display) -> CredentialError {
+    CredentialError::new(&format!("invalid JWT: {}", e))
+}
+
+fn encode_part(value: &Value) -> String {
+    base64::encode_config(value.to_string(), URL_SAFE_NO_PAD)
+}
+
+fn decode_part(part: &str) -> Result<Value, CredentialError> {
+    let bytes = base64::decode_config(part, URL_SAFE_NO_PAD).map_err(invalid_jwt)?;
+    serde_json::from_slice(&bytes).map_err(invalid_jwt)
+}
+
+/// encodes the credential as a JWT signed by the signer, the kid of the JWT is its key id
+pub(crate) fn encode(
+    credential: &Value,
+    version: VcVersion,
+    signer: &impl Signer,
+) -> Result<String, CredentialError> {
+    let mut credential = credential.clone();
+    if let Value::Object(m) = &mut credential {
+        m.remove("proof");
+    }
+    let (typ, claims) = match version {
+        VcVersion::V1 => {
+            let date = |field: &str| match credential[field].as_str() {
+                Some(date) => parse_rfc3339(date).map(Some),
+                None => Ok(None),
+            };
+            let mut claims = json!({
+                "iss": issuer_id(&credential["issuer"]),
+                "jti": credential["id"],
+                "nbf": date("issuanceDate")?,
+                "exp": date("expirationDate")?,
+                "vc": credential,
+            });
+            if let Value::Object(m) = &mut claims {
+                m.retain(|_, v| !v.is_null());
+            }
+            (JWT_TYPE, claims)
+        }
+        VcVersion::V2 => (VC_JWT_TYPE, credential),
+    };
+    let header = json!({
+        "alg": signer.algorithm().name(),
+        "typ": typ,
+        "kid": signer.key_id()
+    });
+    let input = format!("{}.{}", encode_part(&header), encode_part(&claims));
+    let signature = signer.sign(input.as_bytes())?;
+    Ok(format!(
+        "{}.{}",
+        input,
+        base64::encode_config(signature, URL_SAFE_NO_PAD)
+    ))
+}
+
+/// decodes a JWT encoded credential, verifying its signature with the key of
+/// the kid, that must be an assertionMethod of the issuer of the credential
+pub(crate) fn decode(jwt: &str, resolver: &impl DidResolver) -> Result<Value, CredentialError> {
+    let parts: Vec<&str> = jwt.split('.').collect();
+    let [header, payload, signature] = parts[..] else {
+        return Err(invalid_jwt("a JWT has three parts"));
+    };
+    let (header, mut claims) = (decode_part(header)?, decode_part(payload)?);
+    let alg = header["alg"].as_str().unwrap_or_default();
+    let algorithm = Algorithm::from_name(alg)
+        .ok_or_else(|| invalid_jwt(format!("unsupported algorithm {:?}", alg)))?;
+    let kid = header["kid"]
+        .as_str()
+        .ok_or_else(|| invalid_jwt("the header has no kid"))?;
+    let method = resolve_verification_method(resolver, kid, ASSERTION_METHOD)?;
+    if Algorithm::of(&method.key) != algorithm {
+        return Err(invalid_jwt(format!(
+            "the key of {} cannot verify {} signatures",
+            kid, alg
+        )));
+    }
+    let signature = base64::decode_config(signature, URL_SAFE_NO_PAD).map_err(invalid_jwt)?;
+    let input = &jwt[..jwt.rfind('.').unwrap_or_default()];
+    verify_signature(&method.key, input.as_bytes(), &signature)?;
+
+    // the registered claims of a VC 1.1 JWT replace the properties of the credential
+    let mut credential = match claims.get_mut("vc").map(Value::take) {
+        Some(mut vc) => {
+            if !vc.is_object() {
+                return Err(invalid_jwt("the vc claim is not an object"));
+            }
+            let claim = |name: &str| claims.get(name).filter(|c| !c.is_null());
+            if let Some(jti) = claim("jti") {
+                vc["id"] = jti.clone();
+            }
+            match (claim("iss"), issuer_id(&vc["issuer"])) {
+                (Some(iss), None) => vc["issuer"] = iss.clone(),
+                (Some(iss), Some(issuer)) if iss.as_str() != Some(issuer.as_str()) => {
+                    return Err(invalid_jwt(format!(
+                        "the iss claim {} is not the issuer {}",
+                        iss, issuer
+                    )))
+                }
+                _ => {}
+            }
+            for (name, property) in [("nbf", "issuanceDate"), ("exp", "expirationDate")] {
+                if let Some(ts) = claim(name) {
+                    let ts = ts
+                        .as_u64()
+                        .ok_or_else(|| invalid_jwt(format!("the {} claim is not a date", name)))?;
+                    vc[property] = format_rfc3339(ts).into();
+                }
+            }
+            vc
+        }
+        None => claims,
+    };
+    let issuer = issuer_id(&credential["issuer"])
+        .ok_or_else(|| CredentialError::new("the revocation list credential has no issuer"))?;
+    if method.controller != issuer {
+        return Err(CredentialError::new(&format!(
+            "the JWT is signed by {}, not by the issuer {}",
+            method.controller, issuer
+        )));
+    }
+    if let Value::Object(m) = &mut credential {
+        m.remove("proof");
+    }
+    Ok(credential)
+}
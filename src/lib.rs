@@ -1,15 +1,116 @@
-use base64::{decode_config, encode_config, STANDARD};
-use flate2::read::ZlibDecoder;
-use flate2::write::ZlibEncoder;
-use flate2::Compression;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
 use serde_derive::{Deserialize, Serialize};
-use std::fmt::{Display, Formatter};
-use std::io::prelude::*;
+#[cfg(feature = "std")]
 use std::str::FromStr;
-use std::vec::Vec;
-use wasm_bindgen::prelude::*;
+#[cfg(all(feature = "std", not(all(feature = "wasm", target_arch = "wasm32"))))]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+mod bitstring;
+#[cfg(feature = "std")]
+mod checker;
+#[cfg(feature = "std")]
+mod credential;
+#[cfg(feature = "std")]
+mod datetime;
+mod deflate;
+#[cfg(feature = "proof")]
+mod did;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+mod issuer;
+#[cfg(feature = "std")]
+mod journal;
+#[cfg(feature = "std")]
+mod jsonld;
+#[cfg(feature = "proof")]
+mod jwt;
+#[cfg(feature = "std")]
+mod metadata;
+#[cfg(feature = "pkcs11")]
+mod pkcs11;
+#[cfg(feature = "proof")]
+mod proof;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "proof")]
+mod rdfc;
+#[cfg(feature = "std")]
+mod scheduler;
+#[cfg(feature = "proof")]
+mod signer;
+#[cfg(feature = "std")]
+mod snapshot;
+#[cfg(feature = "std")]
+mod status;
+#[cfg(feature = "std")]
+mod stream;
+#[cfg(feature = "wasm")]
+mod wasm;
+
+#[cfg(feature = "std")]
+use bitstring::BitOp;
+pub use bitstring::{Bitstring, Compression, CompressionStats};
+#[cfg(feature = "std")]
+pub use checker::{StatusCheck, StatusChecker, StatusResult};
+#[cfg(feature = "std")]
+pub use credential::{
+    BasicCredentialStatus, RevocationList2020Credential, SimpleCredential, VcVersion,
+};
+#[cfg(feature = "proof")]
+pub use did::{
+    resolve_verification_method, DidKeyResolver, DidResolver, DidResolvers, DidWebResolver,
+    DirFetcher, Fetcher, PublicKey, VerificationMethod,
+};
+#[cfg(feature = "std")]
+pub use issuer::IssuerPolicy;
+#[cfg(feature = "std")]
+pub use journal::{Journal, JournalEntry};
+#[cfg(feature = "std")]
+pub use jsonld::{
+    check_contexts, expand, DocumentLoader, ExpandedCredential, StaticLoader,
+    CREDENTIALS_V1_CONTEXT, CREDENTIALS_V2_CONTEXT, DATA_INTEGRITY_V2_CONTEXT,
+    REVOCATION_LIST_2020_CONTEXT, STATUS_LIST_2021_CONTEXT,
+};
+#[cfg(feature = "std")]
+pub use metadata::{MetadataStore, RevocationMetadata, RevocationReason};
+#[cfg(feature = "pkcs11")]
+pub use pkcs11::{Pkcs11Key, Pkcs11Module, Pkcs11Slot, Pkcs11TokenSession};
+#[cfg(feature = "proof")]
+pub use proof::{
+    Cryptosuite, DATA_INTEGRITY_PROOF, ECDSA_JCS_2019, ECDSA_RDFC_2019, EDDSA_JCS_2022,
+};
+#[cfg(feature = "std")]
+pub use scheduler::{Clock, ScheduledTransition, Scheduler, SystemClock};
+#[cfg(feature = "proof")]
+pub use signer::{
+    Algorithm, MemorySigner, Pkcs11Mechanism, Pkcs11Session, Pkcs11Signer, PrivateKey, Signer,
+};
+#[cfg(feature = "std")]
+pub use snapshot::{MemorySnapshotStore, Snapshot, SnapshotStore};
+#[cfg(feature = "std")]
+pub use status::{
+    find_status_entries, BitstringStatusListEntry, RevocationList2020Status, StatusList2021Entry,
+};
+#[cfg(feature = "std")]
+pub use stream::{decode_stream, encode_stream, lookup_status};
+#[cfg(feature = "wasm")]
+pub use wasm::{
+    init_panic_hook, is_revoked, reset_credential, revoke_credential, WasmRevocationList,
+};
 
+#[cfg(feature = "std")]
 const REVOCATION_LIST_2020_TYPE: &str = "RevocationList2020";
+#[cfg(feature = "std")]
 const REVOCATION_LIST_2020_STATUS_TYPE: &str = "RevocationList2020Status";
 // Minimum bitstring size is 16kb
 const MIN_BITSTRING_SIZE_KN: usize = 16;
@@ -28,6 +129,130 @@ pub trait CredentialStatus {
     /// - ID
     /// - Type
     fn type_def(&self) -> (String, String);
+    /// returns the purpose of the credential status, revocation by default
+    fn purpose(&self) -> StatusPurpose {
+        StatusPurpose::Revocation
+    }
+    /// returns all the status entries of the credential, by default
+    /// the single entry described by type_def, coordinates and purpose
+    fn status_entries(&self) -> Vec<StatusEntry> {
+        let (id, typ) = self.type_def();
+        let (list, index) = self.coordinates();
+        vec![StatusEntry {
+            id,
+            typ,
+            purpose: self.purpose(),
+            list,
+            index,
+        }]
+    }
+    /// returns the id of the issuer of the credential, if known, to check
+    /// that the status lists are published by the same issuer
+    fn issuer(&self) -> Option<String> {
+        None
+    }
+}
+
+/// StatusPurpose is the purpose of a status entry, that is
+/// what it means for the entry to be set in its list
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum StatusPurpose {
+    Revocation,
+    Suspension,
+    Other(String),
+}
+
+impl From<&str> for StatusPurpose {
+    fn from(s: &str) -> Self {
+        match s {
+            "revocation" => StatusPurpose::Revocation,
+            "suspension" => StatusPurpose::Suspension,
+            _ => StatusPurpose::Other(String::from(s)),
+        }
+    }
+}
+
+impl Display for StatusPurpose {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        match self {
+            StatusPurpose::Revocation => write!(f, "revocation"),
+            StatusPurpose::Suspension => write!(f, "suspension"),
+            StatusPurpose::Other(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// StatusEntry is a single entry of the credentialStatus of a credential
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusEntry {
+    pub id: String,
+    pub typ: String,
+    pub purpose: StatusPurpose,
+    /// the id of the list the entry refers to
+    pub list: String,
+    /// the index of the credential within the list
+    pub index: u64,
+}
+
+impl CredentialStatus for StatusEntry {
+    fn coordinates(&self) -> (String, u64) {
+        (self.list.clone(), self.index)
+    }
+
+    fn type_def(&self) -> (String, String) {
+        (self.id.clone(), self.typ.clone())
+    }
+
+    fn status_entries(&self) -> Vec<StatusEntry> {
+        vec![self.clone()]
+    }
+}
+
+// unix_now returns the current unix timestamp in seconds
+#[cfg(all(feature = "std", not(all(feature = "wasm", target_arch = "wasm32"))))]
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+// on wasm32 SystemTime::now panics, the time is read from javascript
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+pub(crate) fn unix_now() -> u64 {
+    (js_sys::Date::now() / 1000.0) as u64
+}
+
+// check_status finds the RevocationList2020Status entry of the credential that
+// points to the revocation list with the given id, returning the index of the
+// credential within the list
+#[cfg(feature = "std")]
+fn check_status(credential: &impl CredentialStatus, list_id: &str) -> Result<u64, CredentialError> {
+    let entries: Vec<StatusEntry> = credential
+        .status_entries()
+        .into_iter()
+        .filter(|e| e.typ == REVOCATION_LIST_2020_STATUS_TYPE)
+        .collect();
+    // check type
+    if entries.is_empty() {
+        return Err(CredentialError::new(&format!(
+            "credential status type doesn't match {}",
+            REVOCATION_LIST_2020_STATUS_TYPE
+        )));
+    }
+    // check coordinates
+    match entries.iter().find(|e| e.list == list_id) {
+        Some(entry) => Ok(entry.index),
+        None => Err(CredentialError::new(&format!(
+            "credential status doesn't match the current revocation lists, expected {}, got {}",
+            list_id,
+            entries
+                .iter()
+                .map(|e| e.list.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+        ))),
+    }
 }
 
 #[derive(Debug)]
@@ -45,7 +270,7 @@ impl CredentialError {
 
 impl Display for CredentialError {
     // This trait requires `fmt` with this exact signature.
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
         // Write strictly the first element into the supplied output
         // stream: `f`. Returns `fmt::Result` which indicates whether the
         // operation succeeded or failed. Note that `write!` uses syntax which
@@ -54,12 +279,14 @@ impl Display for CredentialError {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum RevocationStatus {
     Revoke,
     Reset,
 }
 
+#[cfg(feature = "std")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RevocationList2020 {
     #[serde(rename = "id")]
@@ -70,11 +297,20 @@ pub struct RevocationList2020 {
     encoded_list: String,
     #[serde(skip)]
     bit_set: Vec<u8>,
+    #[serde(skip)]
+    journal: Option<Journal>,
+    #[serde(skip)]
+    version: u64,
+    #[serde(skip)]
+    metadata: Option<MetadataStore>,
+    #[serde(skip)]
+    compression: Compression,
 }
 
+#[cfg(feature = "std")]
 impl Display for RevocationList2020 {
     // This trait requires `fmt` with this exact signature.
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
         match serde_json::to_string(self) {
             Ok(s) => write!(f, "{}", s),
             Err(_) => Err(std::fmt::Error),
@@ -82,12 +318,34 @@ impl Display for RevocationList2020 {
     }
 }
 
+#[cfg(feature = "std")]
 impl FromStr for RevocationList2020 {
     type Err = CredentialError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut rl =
-            serde_json::from_str::<Self>(s).map_err(|e| CredentialError::new(&e.to_string()))?;
+        serde_json::from_str::<Self>(s)
+            .map_err(|e| CredentialError::new(&e.to_string()))?
+            .decode()
+    }
+}
+
+#[cfg(feature = "std")]
+impl RevocationList2020 {
+    // decode validates a deserialized revocation list and decodes its bit string
+    fn decode(self) -> Result<Self, CredentialError> {
+        self.decode_with(Self::unpack)
+    }
+
+    // decode_strict is decode with the strict validation of the encodedList
+    fn decode_strict(self) -> Result<Self, CredentialError> {
+        self.decode_with(bitstring::decode_strict)
+    }
+
+    fn decode_with(
+        self,
+        unpack: fn(&str) -> Result<Vec<u8>, CredentialError>,
+    ) -> Result<Self, CredentialError> {
+        let mut rl = self;
         // check the revocation list id
         if rl.id.trim().is_empty() {
             return Err(CredentialError::new("revocation list id cannot be empty"));
@@ -97,126 +355,260 @@ impl FromStr for RevocationList2020 {
             return Err(CredentialError::new("unrecognized revocation list typoe"));
         }
         // decode the bit string
-        rl.bit_set = RevocationList2020::unpack(&rl.encoded_list)?;
+        rl.bit_set = unpack(&rl.encoded_list)?;
         Ok(rl)
     }
 }
 
+#[cfg(feature = "std")]
 impl RevocationList2020 {
-    fn pack(data: &Vec<u8>) -> Result<String, CredentialError> {
-        // compress the data
-        let mut e = ZlibEncoder::new(Vec::new(), Compression::default());
-        // TODO: handle errors
-        e.write_all(data)
-            .map_err(|e| CredentialError::new(&e.to_string()))?;
-        let compressed = e
-            .finish()
-            .map_err(|e| CredentialError::new(&e.to_string()))?;
-        // encode the data
-        Ok(encode_config(&compressed, STANDARD))
-    }
-
-    fn unpack(data: &String) -> Result<Vec<u8>, CredentialError> {
-        let bin =
-            decode_config(&data, STANDARD).map_err(|e| CredentialError::new(&e.to_string()))?;
-        let mut d = ZlibDecoder::new(&*bin);
-        let mut buf = Vec::new();
-        d.read_to_end(&mut buf)
-            .map_err(|e| CredentialError::new(&e.to_string()))?;
-        Ok(buf)
+    fn pack(data: &[u8], compression: Compression) -> Result<String, CredentialError> {
+        bitstring::encode_with(data, compression)
+    }
+
+    fn unpack(data: &str) -> Result<Vec<u8>, CredentialError> {
+        bitstring::decode(data)
     }
 
     fn check_bounds(&self, index: u64) -> Result<(), CredentialError> {
-        match index {
-            i if (i as usize) >= self.capacity() => Err(CredentialError::new(&format!(
-                "max indexable element is {}, provided index {} is out of range",
-                self.capacity(),
-                i,
-            ))),
-            _ => Ok(()),
-        }
+        bitstring::check_bounds(&self.bit_set, index)
     }
 
-    pub fn new(id: &str, size: usize) -> Result<Self, CredentialError> {
-        if size < MIN_BITSTRING_SIZE_KN {
+    /// parses a list that cannot be trusted, e.g. published by a third party,
+    /// on top of the checks of from_str the encodedList must be canonical base64
+    /// of a single zlib stream of a bitstring within the size limits of the spec
+    pub fn from_str_strict(s: &str) -> Result<Self, CredentialError> {
+        serde_json::from_str::<Self>(s)
+            .map_err(|e| CredentialError::new(&e.to_string()))?
+            .decode_strict()
+    }
+
+    /// checks strictly the encodedList, as from_str_strict does,
+    /// and that it matches the bitset of the list
+    pub fn validate(&self) -> Result<(), CredentialError> {
+        let bits = bitstring::decode_strict(&self.encoded_list)?;
+        if bits.len() != self.bit_set.len() {
             return Err(CredentialError::new(&format!(
-                "minimum credential size is {}, got {}",
-                MIN_BITSTRING_SIZE_KN, size
+                "the encodedList is {} bytes, the bitset is {} bytes",
+                bits.len(),
+                self.bit_set.len()
             )));
         }
-        if size > MAX_BITSTRING_SIZE_KB {
-            return Err(CredentialError::new(&format!(
-                "maximum credential size is {}, got {}",
-                MIN_BITSTRING_SIZE_KN, size
-            )));
+        match bitstring::diff(&bits, &self.bit_set) {
+            (set, reset) if set.is_empty() && reset.is_empty() => Ok(()),
+            (set, reset) => Err(CredentialError::new(&format!(
+                "the encodedList doesn't match the bitset starting from index {}",
+                set.first().into_iter().chain(reset.first()).min().unwrap()
+            ))),
         }
+    }
+
+    pub fn new(id: &str, size: usize) -> Result<Self, CredentialError> {
+        // initialize the bitset
+        let bs = Bitstring::new(size)?.into_bytes();
         if id.trim().is_empty() {
             return Err(CredentialError::new("revocation list id cannot be empty"));
         }
-        // initialize the bitset
-        let bs = vec![0; size * 1024];
-        let el = Self::pack(&bs)?;
+        let el = Self::pack(&bs, Compression::Default)?;
 
         Ok(RevocationList2020 {
             id: String::from(id),
             typ: String::from(REVOCATION_LIST_2020_TYPE),
             encoded_list: el,
             bit_set: bs,
+            journal: None,
+            version: 0,
+            metadata: None,
+            compression: Compression::Default,
         })
     }
 
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
     pub fn capacity(&self) -> usize {
         self.bit_set.len() * 8
     }
 
     // size returns the size of the bitset int kb
     pub fn size(&self) -> usize {
-        return self.bit_set.len() / 1024;
+        self.bit_set.len() / 1024
     }
 
-    pub fn update(&mut self, action: RevocationStatus, index: u64) -> Result<(), CredentialError> {
-        self.check_bounds(index)?;
+    /// sets the compression of the encodedList, that is used from now on
+    /// whenever the list changes, and re-encodes the list with it.
+    /// The changes of a list with the Best compression use the default one,
+    /// encode_for_publication applies it before publishing the list.
+    /// Lists are created and loaded with the default compression
+    pub fn set_compression(&mut self, compression: Compression) -> Result<(), CredentialError> {
+        self.encoded_list = Self::pack(&self.bit_set, compression)?;
+        self.compression = compression;
+        Ok(())
+    }
 
-        let pos = (index / 8) as usize;
-        let j = (index % 8) as u8;
+    /// re-encodes the encodedList with the compression of the list, including
+    /// the Best compression, that is too slow to be applied at every change
+    pub fn encode_for_publication(&mut self) -> Result<(), CredentialError> {
+        self.encoded_list = Self::pack(&self.bit_set, self.compression)?;
+        Ok(())
+    }
 
-        match action {
-            RevocationStatus::Revoke => self.bit_set[pos] |= 1 << j,
-            RevocationStatus::Reset => self.bit_set[pos] &= !(1 << j),
-        };
-        self.encoded_list = Self::pack(&self.bit_set)?;
+    // repack encodes the encodedList after a change
+    fn repack(&mut self) -> Result<(), CredentialError> {
+        self.encoded_list = Self::pack(&self.bit_set, self.compression.for_updates())?;
         Ok(())
     }
 
-    pub fn get(&self, index: u64) -> Result<RevocationStatus, CredentialError> {
-        self.check_bounds(index)?;
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+
+    /// returns the JSON of the list canonicalized according to RFC 8785 (JCS),
+    /// to be used with the deterministic compression when the list is signed
+    pub fn to_canonical_json(&self) -> Result<String, CredentialError> {
+        serde_jcs::to_string(self).map_err(|e| CredentialError::new(&e.to_string()))
+    }
 
-        let pos = (index / 8) as usize;
-        let j = (index % 8) as u8;
+    /// returns the size of the bitset compared to the size of the encodedList
+    pub fn compression_stats(&self) -> CompressionStats {
+        CompressionStats::new(self.bit_set.len(), &self.encoded_list)
+    }
 
-        match self.bit_set[pos] & (1 << j) {
-            0 => Ok(RevocationStatus::Reset),
-            _ => Ok(RevocationStatus::Revoke),
+    pub fn update(&mut self, action: RevocationStatus, index: u64) -> Result<(), CredentialError> {
+        self.update_as(action, index, "", "")
+    }
+
+    /// same as update, but when the journal is enabled the change is recorded
+    /// together with who made it and why. Updates that don't change the
+    /// status of the index are not recorded
+    pub fn update_as(
+        &mut self,
+        action: RevocationStatus,
+        index: u64,
+        actor: &str,
+        reason: &str,
+    ) -> Result<(), CredentialError> {
+        self.apply([(action, index, actor, reason)])
+    }
+
+    /// applies the same action to all the indexes as a single mutation,
+    /// if any index is out of range the list is left unchanged
+    pub fn update_many(
+        &mut self,
+        action: RevocationStatus,
+        indexes: &[u64],
+    ) -> Result<(), CredentialError> {
+        self.apply(indexes.iter().map(|i| (action, *i, "", "")))
+    }
+
+    // apply changes the status of the indexes, in order, as a single mutation,
+    // recording in the journal the changes with who made them and why.
+    // If any index is out of range the list is left unchanged
+    fn apply<'a>(
+        &mut self,
+        changes: impl IntoIterator<Item = (RevocationStatus, u64, &'a str, &'a str)>,
+    ) -> Result<(), CredentialError> {
+        let changes: Vec<_> = changes.into_iter().collect();
+        for (_, index, _, _) in &changes {
+            self.check_bounds(*index)?;
+        }
+        let mut entries = Vec::new();
+        for (action, index, actor, reason) in changes {
+            let old = self.set(action, index)?;
+            if old != action {
+                entries.push(JournalEntry::new(index, old, action, actor, reason));
+            }
+        }
+        if entries.is_empty() {
+            return Ok(());
         }
+        self.version += 1;
+        entries.into_iter().for_each(|entry| self.record(entry));
+        self.repack()
     }
 
-    fn check_ids(&self, credential: &impl CredentialStatus) -> Result<u64, CredentialError> {
-        // check type
-        if credential.type_def().1 != REVOCATION_LIST_2020_STATUS_TYPE {
-            return Err(CredentialError::new(&format!(
-                "credential status type doesn't match {}",
-                REVOCATION_LIST_2020_STATUS_TYPE
-            )));
+    // set changes the bit at index without repacking the encoded list,
+    // returning the status before the change
+    fn set(
+        &mut self,
+        action: RevocationStatus,
+        index: u64,
+    ) -> Result<RevocationStatus, CredentialError> {
+        let old = bitstring::set(&mut self.bit_set, action, index)?;
+        // the metadata only describes revoked indexes
+        if action == RevocationStatus::Reset {
+            if let Some(metadata) = self.metadata.as_mut() {
+                metadata.remove(index);
+            }
         }
-        // check coordinates
-        let coords = credential.coordinates();
-        if coords.0 != self.id {
-            return Err(CredentialError::new(&format!(
-                "credential status doesn't match the current revocation lists, expected {}, got {}",
-                self.id, coords.0,
-            )));
+        Ok(old)
+    }
+
+    // record appends the entry to the journal, if enabled, with the current version
+    fn record(&mut self, entry: JournalEntry) {
+        if let Some(journal) = self.journal.as_mut() {
+            journal.push(JournalEntry {
+                version: self.version,
+                ..entry
+            });
+        }
+    }
+
+    /// starts recording every status change into a journal,
+    /// it has no effect if the journal is already enabled
+    pub fn enable_journal(&mut self) {
+        if self.journal.is_none() {
+            self.journal = Some(Journal::default());
         }
-        Ok(coords.1)
+    }
+
+    /// returns the journal of the status changes, if enabled
+    pub fn journal(&self) -> Option<&Journal> {
+        self.journal.as_ref()
+    }
+
+    /// returns the version of the list, that is incremented once for every
+    /// mutation that changes the bitset, however many indexes it changes
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    // apply_diff changes the status of all the indexes in the diff as a single
+    // mutation, recording each change in the journal
+    fn apply_diff(
+        &mut self,
+        diff: RevocationListDiff,
+        reason: &str,
+    ) -> Result<(), CredentialError> {
+        let revoked = diff
+            .revoked
+            .into_iter()
+            .map(|i| (RevocationStatus::Revoke, i));
+        let reset = diff.reset.into_iter().map(|i| (RevocationStatus::Reset, i));
+        self.apply(
+            revoked
+                .chain(reset)
+                .map(|(action, i)| (action, i, "", reason)),
+        )
+    }
+
+    pub fn get(&self, index: u64) -> Result<RevocationStatus, CredentialError> {
+        bitstring::get(&self.bit_set, index)
+    }
+
+    /// iterates over the revoked indexes in ascending order
+    pub fn revoked_indexes(&self) -> impl Iterator<Item = u64> + '_ {
+        bitstring::iter_ones(&self.bit_set)
+    }
+
+    /// returns the number of revoked indexes
+    pub fn revoked_count(&self) -> u64 {
+        bitstring::count_ones(&self.bit_set)
+    }
+
+    fn check_ids(&self, credential: &impl CredentialStatus) -> Result<u64, CredentialError> {
+        check_status(credential, &self.id)
     }
 
     pub fn revoke(&mut self, credential: &impl CredentialStatus) -> Result<(), CredentialError> {
@@ -229,6 +621,9 @@ impl RevocationList2020 {
             .and_then(|i| self.update(RevocationStatus::Reset, i))
     }
 
+    /// returns true if the credential is revoked in the list. A bare list has
+    /// no issuer, so nothing binds it to the issuer of the credential: use
+    /// RevocationList2020Credential::is_revoked for the lists received from others
     pub fn is_revoked(&self, credential: &impl CredentialStatus) -> Result<bool, CredentialError> {
         self.check_ids(credential).and_then(|i| {
             self.get(i).map(|x| match x {
@@ -237,17 +632,129 @@ impl RevocationList2020 {
             })
         })
     }
+
+    fn check_compatible(&self, other: &RevocationList2020) -> Result<(), CredentialError> {
+        if self.id != other.id {
+            return Err(CredentialError::new(&format!(
+                "revocation list ids don't match, expected {}, got {}",
+                self.id, other.id,
+            )));
+        }
+        if self.bit_set.len() != other.bit_set.len() {
+            return Err(CredentialError::new(&format!(
+                "revocation list sizes don't match, expected {}, got {}",
+                self.size(),
+                other.size(),
+            )));
+        }
+        Ok(())
+    }
+
+    /// returns the indexes that changed status going from this list
+    /// to the other version of the same list
+    pub fn diff(&self, other: &RevocationList2020) -> Result<RevocationListDiff, CredentialError> {
+        self.check_compatible(other)?;
+
+        let (revoked, reset) = bitstring::diff(&self.bit_set, &other.bit_set);
+        Ok(RevocationListDiff { revoked, reset })
+    }
+
+    /// returns a new list where an index is revoked if it is revoked
+    /// in either this list or the other one
+    pub fn union(&self, other: &RevocationList2020) -> Result<RevocationList2020, CredentialError> {
+        self.combine(other, BitOp::Or)
+    }
+
+    /// returns a new list where an index is revoked only if it is revoked
+    /// in both this list and the other one
+    pub fn intersection(
+        &self,
+        other: &RevocationList2020,
+    ) -> Result<RevocationList2020, CredentialError> {
+        self.combine(other, BitOp::And)
+    }
+
+    /// revokes in place all the indexes that are revoked in the other list
+    pub fn merge(&mut self, other: &RevocationList2020) -> Result<(), CredentialError> {
+        let diff = self.diff(&self.union(other)?)?;
+        self.apply_diff(diff, "merge")
+    }
+
+    fn combine(
+        &self,
+        other: &RevocationList2020,
+        op: BitOp,
+    ) -> Result<RevocationList2020, CredentialError> {
+        self.check_compatible(other)?;
+
+        let bs = bitstring::combine(&self.bit_set, &other.bit_set, op);
+        let el = Self::pack(&bs, self.compression.for_updates())?;
+
+        Ok(RevocationList2020 {
+            id: self.id.to_owned(),
+            typ: self.typ.to_owned(),
+            encoded_list: el,
+            bit_set: bs,
+            journal: None,
+            version: 0,
+            metadata: None,
+            compression: self.compression,
+        })
+    }
 }
 
-#[cfg(test)]
+/// RevocationListDiff lists the indexes that changed status
+/// between two versions of the same revocation list
+#[cfg(feature = "std")]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RevocationListDiff {
+    /// indexes that have been revoked in the newer version
+    pub revoked: Vec<u64>,
+    /// indexes that have been reset in the newer version
+    pub reset: Vec<u64>,
+}
+
+#[cfg(feature = "std")]
+impl RevocationListDiff {
+    pub fn is_empty(&self) -> bool {
+        self.revoked.is_empty() && self.reset.is_empty()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+// the original tests compare with bool literals and repeat the field names
+#[allow(clippy::bool_assert_comparison, clippy::redundant_field_names)]
 mod tests {
 
     use super::{
-        CredentialStatus, RevocationList2020, RevocationStatus, REVOCATION_LIST_2020_STATUS_TYPE,
+        Bitstring, Clock, Compression, CredentialStatus, Journal, MemorySnapshotStore,
+        MetadataStore, RevocationList2020, RevocationList2020Credential, RevocationMetadata,
+        RevocationReason, RevocationStatus, Scheduler, SimpleCredential, SnapshotStore,
+        StatusChecker, StatusPurpose, VcVersion, REVOCATION_LIST_2020_STATUS_TYPE,
+    };
+    use crate::bitstring::{self, BitOp};
+    use crate::datetime;
+    use crate::{
+        check_contexts, decode_stream, encode_stream, expand, find_status_entries, lookup_status,
+        BitstringStatusListEntry, ExpandedCredential, IssuerPolicy, StaticLoader,
+        StatusList2021Entry, CREDENTIALS_V1_CONTEXT, CREDENTIALS_V2_CONTEXT,
+        REVOCATION_LIST_2020_CONTEXT, STATUS_LIST_2021_CONTEXT,
     };
     use rand::Rng;
+    use serde_json::{json, Value};
+    use std::cell::Cell;
+    use std::io::Read;
     use std::str::FromStr;
 
+    // TestClock is a clock whose time is set by the tests
+    struct TestClock(Cell<u64>);
+
+    impl Clock for TestClock {
+        fn now(&self) -> u64 {
+            self.0.get()
+        }
+    }
+
     #[test]
     fn test_create() {
         // FAIL: size too big
@@ -418,117 +925,2217 @@ mod tests {
             "eJzswDEBAAAAwiD7pzbGHhgAAAAAAAAAAAAAAAAAAACQewAAAP//QAAAAQ=="
         )
     }
-}
 
-// WASM stuff
+    #[test]
+    fn test_diff() {
+        let mut old = RevocationList2020::new("test-1", 16).unwrap();
+        old.update(RevocationStatus::Revoke, 3).unwrap();
+        old.update(RevocationStatus::Revoke, 42).unwrap();
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SimpleCredential {
-    #[serde(rename = "credentialStatus")]
-    credential_status: BasicCredentialStatus,
-}
+        let mut new = old.clone();
+        new.update(RevocationStatus::Reset, 3).unwrap();
+        new.update(RevocationStatus::Revoke, 9).unwrap();
+        new.update(RevocationStatus::Revoke, 1000).unwrap();
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BasicCredentialStatus {
-    #[serde(rename = "revocationListIndex")]
-    revocation_list_index: u64,
-    #[serde(rename = "revocationListCredential")]
-    revocation_list_credential: String,
-}
+        let diff = old.diff(&new).unwrap();
+        assert_eq!(diff.revoked, vec![9, 1000]);
+        assert_eq!(diff.reset, vec![3]);
+        assert!(old.diff(&old).unwrap().is_empty());
 
-/// To be able to use the rl2020 library it's neccessary
-/// to implement the CredentialStatus trait
-impl CredentialStatus for SimpleCredential {
-    fn coordinates(&self) -> (String, u64) {
-        (
-            self.credential_status.revocation_list_credential.to_owned(),
-            self.credential_status.revocation_list_index.to_owned(),
+        // FAIL: id doesn't match
+        let other = RevocationList2020::new("test-2", 16).unwrap();
+        assert!(old.diff(&other).is_err());
+        // FAIL: size doesn't match
+        let other = RevocationList2020::new("test-1", 17).unwrap();
+        assert!(old.diff(&other).is_err());
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let mut a = RevocationList2020::new("test-1", 16).unwrap();
+        a.update(RevocationStatus::Revoke, 1).unwrap();
+        a.update(RevocationStatus::Revoke, 2).unwrap();
+        let mut b = RevocationList2020::new("test-1", 16).unwrap();
+        b.update(RevocationStatus::Revoke, 2).unwrap();
+        b.update(RevocationStatus::Revoke, 3).unwrap();
+
+        let u = a.union(&b).unwrap();
+        let i = a.intersection(&b).unwrap();
+        for (idx, in_union, in_intersection) in
+            [(1, true, false), (2, true, true), (3, true, false)]
+        {
+            assert_eq!(u.get(idx).unwrap() == RevocationStatus::Revoke, in_union);
+            assert_eq!(
+                i.get(idx).unwrap() == RevocationStatus::Revoke,
+                in_intersection
+            );
+        }
+        // the encoded list must follow the bitset
+        let u2 = RevocationList2020::from_str(&u.to_string()).unwrap();
+        assert!(u.diff(&u2).unwrap().is_empty());
+
+        a.merge(&b).unwrap();
+        assert!(a.diff(&u).unwrap().is_empty());
+
+        // FAIL: id doesn't match
+        let c = RevocationList2020::new("test-2", 16).unwrap();
+        assert!(a.union(&c).is_err());
+        assert!(a.intersection(&c).is_err());
+        assert!(a.merge(&c).is_err());
+    }
+
+    #[test]
+    fn test_journal() {
+        let mut rl = RevocationList2020::new("test-1", 16).unwrap();
+        // the journal is disabled by default
+        rl.update(RevocationStatus::Revoke, 1).unwrap();
+        assert!(rl.journal().is_none());
+
+        rl.enable_journal();
+        rl.update_as(RevocationStatus::Revoke, 7, "alice", "key compromise")
+            .unwrap();
+        rl.update(RevocationStatus::Reset, 1).unwrap();
+        // no change, nothing to record
+        rl.update(RevocationStatus::Reset, 1).unwrap();
+        rl.update_many(RevocationStatus::Revoke, &[7]).unwrap();
+        let mut other = RevocationList2020::new("test-1", 16).unwrap();
+        other.update(RevocationStatus::Revoke, 99).unwrap();
+        rl.merge(&other).unwrap();
+
+        let journal = rl.journal().unwrap();
+        assert_eq!(journal.len(), 3);
+        let entry = &journal.entries()[0];
+        assert_eq!(entry.index, 7);
+        assert_eq!(entry.old_status, RevocationStatus::Reset);
+        assert_eq!(entry.new_status, RevocationStatus::Revoke);
+        assert_eq!(entry.actor, "alice");
+        assert_eq!(entry.reason, "key compromise");
+        assert_eq!(journal.entries()[2].reason, "merge");
+
+        // export and parse back
+        let jsonl = journal.to_jsonl().unwrap();
+        assert_eq!(jsonl.lines().count(), 3);
+        let parsed = Journal::from_jsonl(&jsonl).unwrap();
+        assert_eq!(&parsed, journal);
+        assert!(Journal::from_jsonl("{}").is_err());
+
+        // FAIL: index 1 was revoked before the journal was enabled
+        assert!(parsed.replay("test-1", 16).is_err());
+
+        // PASS: replaying a complete journal rebuilds the list
+        let mut rl = RevocationList2020::new("test-1", 16).unwrap();
+        rl.enable_journal();
+        rl.update(RevocationStatus::Revoke, 1).unwrap();
+        rl.update(RevocationStatus::Revoke, 8000).unwrap();
+        rl.update(RevocationStatus::Reset, 1).unwrap();
+        // a bulk mutation is a single version
+        rl.update_many(RevocationStatus::Revoke, &[2, 3, 4])
+            .unwrap();
+        rl.merge(&other).unwrap();
+        assert_eq!(rl.version(), 5);
+        let versions: Vec<u64> = rl
+            .journal()
+            .unwrap()
+            .entries()
+            .iter()
+            .map(|e| e.version)
+            .collect();
+        assert_eq!(versions, vec![1, 2, 3, 4, 4, 4, 5]);
+        let rebuilt = rl.journal().unwrap().replay("test-1", 16).unwrap();
+        assert!(rl.diff(&rebuilt).unwrap().is_empty());
+        assert_eq!(rl.to_string(), rebuilt.to_string());
+        assert_eq!(rebuilt.journal(), rl.journal());
+        assert_eq!(rebuilt.version(), rl.version());
+    }
+
+    #[test]
+    fn test_snapshot_rollback() {
+        let mut store = MemorySnapshotStore::default();
+        let mut rl = RevocationList2020::new("test-1", 16).unwrap();
+        assert_eq!(rl.version(), 0);
+        assert_eq!(rl.save(&mut store).unwrap(), 0);
+
+        rl.update(RevocationStatus::Revoke, 5).unwrap();
+        // no change, no new version
+        rl.update(RevocationStatus::Revoke, 5).unwrap();
+        assert_eq!(rl.version(), 1);
+        assert_eq!(rl.save(&mut store).unwrap(), 1);
+        // same content, same version
+        assert_eq!(rl.save(&mut store).unwrap(), 1);
+
+        // a bulk revoke by mistake
+        let mut mistake = RevocationList2020::new("test-1", 16).unwrap();
+        for i in 100..200 {
+            mistake.update(RevocationStatus::Revoke, i).unwrap();
+        }
+        rl.enable_journal();
+        rl.merge(&mistake).unwrap();
+        assert_eq!(rl.version(), 2);
+        assert_eq!(rl.save(&mut store).unwrap(), 2);
+        assert_eq!(store.versions("test-1").unwrap(), vec![0, 1, 2]);
+        assert!(store.versions("test-2").unwrap().is_empty());
+
+        // PASS: roll back to version 1
+        rl.rollback(&store, 1).unwrap();
+        assert_eq!(rl.version(), 3);
+        assert_eq!(rl.get(5).unwrap(), RevocationStatus::Revoke);
+        assert_eq!(rl.get(150).unwrap(), RevocationStatus::Reset);
+        assert_eq!(rl.journal().unwrap().len(), 200);
+        let v1 = store.load("test-1", 1).unwrap().to_list().unwrap();
+        assert_eq!(v1.version(), 1);
+        assert!(rl.diff(&v1).unwrap().is_empty());
+        assert_eq!(rl.to_string(), v1.to_string());
+
+        // FAIL: version not in the store
+        assert!(rl.rollback(&store, 42).is_err());
+        // PASS: a list parsed from JSON restarts from version 0,
+        // its changes are stored after the latest version
+        let mut parsed = RevocationList2020::from_str(&rl.to_string()).unwrap();
+        assert_eq!(parsed.version(), 0);
+        parsed.update(RevocationStatus::Revoke, 6).unwrap();
+        assert_eq!(parsed.save(&mut store).unwrap(), 3);
+        assert_eq!(parsed.version(), 3);
+        assert_eq!(
+            store.load("test-1", 1).unwrap().encoded_list,
+            v1.encoded_list
+        );
+        assert_eq!(store.versions("test-1").unwrap(), vec![0, 1, 2, 3]);
+
+        // PASS: the same bitset with another compression is the same snapshot
+        parsed.set_compression(Compression::Deterministic).unwrap();
+        assert_ne!(
+            parsed.encoded_list,
+            store.load("test-1", 3).unwrap().encoded_list
+        );
+        assert_eq!(parsed.save(&mut store).unwrap(), 3);
+        // PASS: the content of an older version doesn't move the version back
+        parsed.update(RevocationStatus::Revoke, 7).unwrap();
+        parsed.update(RevocationStatus::Reset, 7).unwrap();
+        assert_eq!(parsed.version(), 5);
+        assert_eq!(parsed.save(&mut store).unwrap(), 5);
+        assert_eq!(parsed.version(), 5);
+        assert_eq!(store.versions("test-1").unwrap(), vec![0, 1, 2, 3, 5]);
+    }
+
+    #[test]
+    fn test_metadata() {
+        let mut rl = RevocationList2020::new("test-1", 16).unwrap();
+        // FAIL: metadata store not enabled
+        assert!(rl
+            .revoke_with_reason(1, RevocationReason::KeyCompromise)
+            .is_err());
+
+        let mut store = MetadataStore::default();
+        store.insert(
+            1,
+            RevocationMetadata {
+                reason: RevocationReason::KeyCompromise,
+                timestamp: 1_000,
+            },
+        );
+        store.insert(
+            2,
+            RevocationMetadata {
+                reason: RevocationReason::Superseded,
+                timestamp: 2_000,
+            },
+        );
+        rl.set_metadata(store);
+        rl.enable_journal();
+        rl.revoke_with_reason(3, RevocationReason::KeyCompromise)
+            .unwrap();
+        rl.revoke_with_reason(4, RevocationReason::KeyCompromise)
+            .unwrap();
+        assert_eq!(rl.get(3).unwrap(), RevocationStatus::Revoke);
+        assert_eq!(rl.journal().unwrap().entries()[0].reason, "keyCompromise");
+
+        // resetting an index drops its metadata
+        rl.update(RevocationStatus::Reset, 4).unwrap();
+        let metadata = rl.metadata().unwrap();
+        assert!(metadata.get(4).is_none());
+        assert_eq!(
+            metadata.revoked_for(RevocationReason::KeyCompromise, 0),
+            vec![1, 3]
+        );
+        assert_eq!(
+            metadata.revoked_for(RevocationReason::KeyCompromise, 1_500),
+            vec![3]
+        );
+        assert!(metadata
+            .revoked_for(RevocationReason::CessationOfOperation, 0)
+            .is_empty());
+
+        // the metadata is kept out of the published list
+        assert!(!rl.to_string().contains("keyCompromise"));
+        let json = metadata.to_json().unwrap();
+        assert_eq!(&MetadataStore::from_json(&json).unwrap(), metadata);
+    }
+
+    #[test]
+    fn test_scheduler() {
+        let clock = TestClock(Cell::new(1_000));
+        let mut scheduler = Scheduler::new(&clock);
+        let mut rl = RevocationList2020::new("test-1", 16).unwrap();
+
+        scheduler.schedule_revocation(10, 2_000);
+        scheduler.suspend(&mut rl, 20, 500).unwrap();
+        scheduler.schedule_revocation(30, 1_200);
+        assert_eq!(rl.get(20).unwrap(), RevocationStatus::Revoke);
+        let due: Vec<u64> = scheduler.pending().iter().map(|t| t.at).collect();
+        assert_eq!(due, vec![1_200, 1_500, 2_000]);
+
+        // nothing is due yet
+        assert!(scheduler.run(&mut rl).unwrap().is_empty());
+
+        clock.0.set(1_500);
+        let changed = scheduler.run(&mut rl).unwrap();
+        assert_eq!(changed.len(), 2);
+        assert_eq!(changed[0].index, 30);
+        assert_eq!(changed[1].index, 20);
+        assert_eq!(rl.get(30).unwrap(), RevocationStatus::Revoke);
+        assert_eq!(rl.get(20).unwrap(), RevocationStatus::Reset);
+        assert!(!scheduler.is_suspended(20));
+        assert_eq!(scheduler.pending().len(), 1);
+        // the due transitions are a single mutation
+        assert_eq!(rl.version(), 2);
+
+        // cancelled transitions are never applied
+        assert_eq!(scheduler.cancel(10), 1);
+        clock.0.set(5_000);
+        assert!(scheduler.run(&mut rl).unwrap().is_empty());
+        assert_eq!(rl.get(10).unwrap(), RevocationStatus::Reset);
+
+        // a suspended index revoked before the suspension expires stays revoked
+        scheduler.suspend(&mut rl, 50, 100).unwrap();
+        assert!(scheduler.is_suspended(50));
+        scheduler.revoke(&mut rl, 50, "key compromise").unwrap();
+        assert!(!scheduler.is_suspended(50));
+        // FAIL: a revoked index cannot be suspended
+        assert!(scheduler.suspend(&mut rl, 50, 100).is_err());
+        clock.0.set(6_000);
+        assert!(scheduler.run(&mut rl).unwrap().is_empty());
+        assert_eq!(rl.get(50).unwrap(), RevocationStatus::Revoke);
+
+        // the same with a scheduled revocation due together with the expiration
+        scheduler.suspend(&mut rl, 60, 100).unwrap();
+        scheduler.schedule_revocation(60, 6_050);
+        clock.0.set(7_000);
+        assert!(scheduler.run(&mut rl).unwrap().is_empty());
+        assert_eq!(rl.get(60).unwrap(), RevocationStatus::Revoke);
+        assert!(scheduler.pending().is_empty());
+
+        // a reset scheduled by the user at the same time of the expiration
+        // is kept when the suspension is lifted
+        scheduler.suspend(&mut rl, 70, 100).unwrap();
+        let reset = scheduler.schedule(RevocationStatus::Reset, 70, 7_100, "appeal");
+        scheduler.revoke(&mut rl, 70, "key compromise").unwrap();
+        assert_eq!(scheduler.pending().len(), 1);
+        assert_eq!(scheduler.pending()[0].id, reset);
+        clock.0.set(8_000);
+        assert_eq!(scheduler.run(&mut rl).unwrap().len(), 1);
+        assert_eq!(rl.get(70).unwrap(), RevocationStatus::Reset);
+
+        // FAIL: index out of range, nothing changes
+        scheduler.schedule_revocation(40, 5_000);
+        scheduler.schedule_revocation(200_000_000, 5_000);
+        assert!(scheduler.run(&mut rl).is_err());
+        assert_eq!(rl.get(40).unwrap(), RevocationStatus::Reset);
+        assert_eq!(scheduler.pending().len(), 2);
+    }
+
+    #[test]
+    fn test_update_many() {
+        let mut rl = RevocationList2020::new("test-1", 16).unwrap();
+        rl.update_many(RevocationStatus::Revoke, &[1, 2, 3, 1000])
+            .unwrap();
+        assert_eq!(rl.version(), 1);
+        for i in [1, 2, 3, 1000] {
+            assert_eq!(rl.get(i).unwrap(), RevocationStatus::Revoke);
+        }
+        let parsed = RevocationList2020::from_str(&rl.to_string()).unwrap();
+        assert!(rl.diff(&parsed).unwrap().is_empty());
+
+        rl.update_many(RevocationStatus::Reset, &[2, 3]).unwrap();
+        assert_eq!(rl.version(), 2);
+        assert_eq!(rl.get(1).unwrap(), RevocationStatus::Revoke);
+        assert_eq!(rl.get(2).unwrap(), RevocationStatus::Reset);
+
+        // FAIL: one index out of range, nothing changes
+        assert!(rl
+            .update_many(RevocationStatus::Revoke, &[4, 200_000_000])
+            .is_err());
+        assert_eq!(rl.get(4).unwrap(), RevocationStatus::Reset);
+        assert_eq!(rl.version(), 2);
+        assert_eq!(rl.revoked_indexes().collect::<Vec<u64>>(), vec![1, 1000]);
+    }
+
+    #[test]
+    fn test_simple_credential() {
+        // PASS: single entry
+        let cr = serde_json::from_str::<SimpleCredential>(
+            r#"{
+            "credentialStatus": {
+                "id": "https://example.com/status/3#94567",
+                "type": "RevocationList2020Status",
+                "revocationListIndex": "94567",
+                "revocationListCredential": "https://example.com/status/3"
+            }
+        }"#,
         )
+        .unwrap();
+        assert_eq!(
+            cr.coordinates(),
+            ("https://example.com/status/3".to_owned(), 94567)
+        );
+        assert_eq!(cr.type_def().0, "https://example.com/status/3#94567");
+
+        // PASS: array of entries, other status types are ignored
+        let cr = serde_json::from_str::<SimpleCredential>(
+            r#"{
+            "credentialStatus": [
+                {
+                    "type": "StatusList2021Entry",
+                    "statusListIndex": "1",
+                    "statusListCredential": "https://example.com/status/1"
+                },
+                {
+                    "type": "RevocationList2020Status",
+                    "revocationListIndex": 7,
+                    "revocationListCredential": "https://example.com/status/2"
+                },
+                {
+                    "type": "RevocationList2020Status",
+                    "revocationListIndex": 8,
+                    "revocationListCredential": "https://example.com/status/3"
+                }
+            ]
+        }"#,
+        )
+        .unwrap();
+        assert_eq!(cr.entries().len(), 2);
+        assert_eq!(
+            cr.status_for("https://example.com/status/3")
+                .unwrap()
+                .coordinates()
+                .1,
+            8
+        );
+        assert!(cr.status_for("https://example.com/status/1").is_none());
+
+        // FAIL: no RevocationList2020Status entry
+        let cr = serde_json::from_str::<SimpleCredential>(
+            r#"{
+            "credentialStatus": {
+                "type": "StatusList2021Entry",
+                "statusListIndex": "1",
+                "statusListCredential": "https://example.com/status/1"
+            }
+        }"#,
+        );
+        assert!(cr.is_err());
     }
 
-    fn type_def(&self) -> (String, String) {
-        ("42".to_owned(), REVOCATION_LIST_2020_STATUS_TYPE.to_owned())
+    #[test]
+    fn test_status_checker() {
+        let cr = serde_json::from_str::<SimpleCredential>(
+            r#"{
+            "credentialStatus": [
+                {
+                    "type": "RevocationList2020Status",
+                    "revocationListIndex": 7,
+                    "revocationListCredential": "https://example.com/status/revocation"
+                },
+                {
+                    "type": "RevocationList2020Status",
+                    "statusPurpose": "suspension",
+                    "revocationListIndex": 8,
+                    "revocationListCredential": "https://example.com/status/suspension"
+                }
+            ]
+        }"#,
+        )
+        .unwrap();
+        let entries = cr.status_entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].purpose, StatusPurpose::Revocation);
+        assert_eq!(entries[1].purpose, StatusPurpose::Suspension);
+
+        let mut revocations =
+            RevocationList2020::new("https://example.com/status/revocation", 16).unwrap();
+        let mut suspensions =
+            RevocationList2020::new("https://example.com/status/suspension", 16).unwrap();
+
+        // FAIL: the suspension list is missing
+        let mut checker = StatusChecker::new();
+        checker.add_unbound_list(&revocations);
+        assert!(checker.check(&cr).is_err());
+
+        let mut checker = StatusChecker::new();
+        checker.add_unbound_list(&revocations);
+        checker.add_unbound_list(&suspensions);
+        let check = checker.check(&cr).unwrap();
+        assert!(check.is_valid());
+        assert_eq!(check.results.len(), 2);
+
+        suspensions.update(RevocationStatus::Revoke, 8).unwrap();
+        revocations.update(RevocationStatus::Revoke, 8).unwrap();
+        let mut checker = StatusChecker::new();
+        checker.add_unbound_list(&revocations);
+        checker.add_unbound_list(&suspensions);
+        let check = checker.check(&cr).unwrap();
+        assert!(!check.is_valid());
+        assert!(check.is_suspended());
+        assert!(!check.is_revoked());
     }
-}
 
-#[wasm_bindgen]
-pub fn init_panic_hook() {
-    console_error_panic_hook::set_once();
-}
+    #[test]
+    fn test_json_credential_status() {
+        let mut vc: serde_json::Value = serde_json::from_str(
+            r#"{
+            "@context": ["https://www.w3.org/2018/credentials/v1"],
+            "id": "https://example.com/credentials/23894672394",
+            "type": ["VerifiableCredential"],
+            "issuer": "did:example:12345",
+            "credentialStatus": [
+                {
+                    "id": "https://example.com/status/1#94567",
+                    "type": "StatusList2021Entry",
+                    "statusPurpose": "suspension",
+                    "statusListIndex": "94567",
+                    "statusListCredential": "https://example.com/status/1"
+                },
+                {
+                    "id": "https://example.com/status/2#42",
+                    "type": "BitstringStatusListEntry",
+                    "statusPurpose": "revocation",
+                    "statusListIndex": "42",
+                    "statusListCredential": "https://example.com/status/2"
+                },
+                {
+                    "id": "https://example.com/status/3#7812",
+                    "type": "RevocationList2020Status",
+                    "revocationListIndex": "7812",
+                    "revocationListCredential": "https://example.com/status/3"
+                }
+            ]
+        }"#,
+        )
+        .unwrap();
 
-#[wasm_bindgen]
-pub fn is_revoked(
-    revocation_list_credential: &str,
-    subject_credential: &str,
-) -> Result<bool, JsValue> {
-    let rl = RevocationList2020::from_str(revocation_list_credential).map_err(|err| {
-        JsValue::from(&format!(
-            "error parsing the revocation list: {}",
-            err.to_string()
-        ))
-    })?;
-    let cr = serde_json::from_str::<SimpleCredential>(subject_credential).map_err(|err| {
-        JsValue::from(&format!(
-            "error parsing the input credential: {}",
-            err.to_string()
-        ))
-    })?;
-    rl.is_revoked(&cr).map_err(|err| {
-        JsValue::from(&format!(
-            "error checking the revocation status: {}",
-            err.to_string()
-        ))
-    })
-}
+        let entries = find_status_entries(&vc).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].purpose, StatusPurpose::Suspension);
+        assert_eq!(entries[0].index, 94567);
+        assert_eq!(entries[1].list, "https://example.com/status/2");
+        // the entry can be given directly too
+        assert_eq!(
+            find_status_entries(&vc["credentialStatus"][2]).unwrap()[0],
+            entries[2]
+        );
 
-#[wasm_bindgen]
-pub fn revoke_credential(
-    revocation_list_credential: &str,
-    subject_credential: &str,
-) -> Result<String, JsValue> {
-    let mut rl = RevocationList2020::from_str(revocation_list_credential).map_err(|err| {
-        JsValue::from(&format!(
-            "error parsing the revocation list: {}",
-            err.to_string()
-        ))
-    })?;
-    let cr = serde_json::from_str::<SimpleCredential>(subject_credential).map_err(|err| {
-        JsValue::from(&format!(
-            "error parsing the input credential: {}",
-            err.to_string()
-        ))
-    })?;
-    rl.revoke(&cr).map_err(|err| {
-        JsValue::from(&format!(
-            "error checking the revocation status: {}",
-            err.to_string()
-        ))
-    })?;
-    Ok(rl.to_string())
-}
+        // PASS: the raw credential is checked against its RevocationList2020Status entry
+        let mut rl = RevocationList2020::new("https://example.com/status/3", 16).unwrap();
+        assert!(!rl.is_revoked(&vc).unwrap());
+        rl.revoke(&vc).unwrap();
+        assert!(rl.is_revoked(&vc).unwrap());
+
+        // the typed entries parse on their own
+        let e: StatusList2021Entry =
+            serde_json::from_value(vc["credentialStatus"][0].clone()).unwrap();
+        assert_eq!(
+            e.coordinates(),
+            ("https://example.com/status/1".to_owned(), 94567)
+        );
+        let e: BitstringStatusListEntry =
+            serde_json::from_value(vc["credentialStatus"][1].clone()).unwrap();
+        assert_eq!(e.status_entries()[0].purpose, StatusPurpose::Revocation);
+
+        // the entries of unsupported types are skipped
+        vc["credentialStatus"][0]["type"] = "UnknownStatus".into();
+        let entries = find_status_entries(&vc).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].list, "https://example.com/status/2");
+        assert!(rl.is_revoked(&vc).unwrap());
+        // the entry is selected by the id of the list
+        vc["credentialStatus"][1]["type"] = "RevocationList2020Status".into();
+        vc["credentialStatus"][1]["revocationListIndex"] = "42".into();
+        vc["credentialStatus"][1]["revocationListCredential"] =
+            "https://example.com/status/2".into();
+        assert!(rl.is_revoked(&vc).unwrap());
+        let other = RevocationList2020::new("https://example.com/status/2", 16).unwrap();
+        assert!(!other.is_revoked(&vc).unwrap());
+        // FAIL: no entry refers to the list
+        let other = RevocationList2020::new("https://example.com/status/4", 16).unwrap();
+        assert!(other.is_revoked(&vc).is_err());
+        // FAIL: no supported status type
+        for i in 1..3 {
+            vc["credentialStatus"][i]["type"] = "UnknownStatus".into();
+        }
+        assert!(find_status_entries(&vc).is_err());
+        assert!(rl.is_revoked(&vc).is_err());
+        // FAIL: no credentialStatus at all
+        let vc = serde_json::json!({"id": "https://example.com/credentials/1"});
+        assert!(find_status_entries(&vc).is_err());
+        assert!(rl.is_revoked(&vc).is_err());
+        let mut checker = StatusChecker::new();
+        checker.add_unbound_list(&rl);
+        assert!(checker.check(&vc).is_err());
+    }
+
+    #[test]
+    fn test_revocation_list_credential() {
+        let data = r#"
+        {
+            "@context": [
+                "https://www.w3.org/2018/credentials/v1",
+                "https://w3id.org/vc-revocation-list-2020/v1"
+            ],
+            "id": "https://example.com/credentials/status/3",
+            "type": ["VerifiableCredential", "RevocationList2020Credential"],
+            "issuer": "did:example:12345",
+            "issued": "2020-04-05T14:27:42Z",
+            "credentialSubject": {
+                "id": "https://example.com/credentials/status/3#list",
+                "type": "RevocationList2020",
+                "encodedList": "eJzswDEBAAAAwiD7pzbGHhgAAAAAAAAAAAAAAAAAAACQewAAAP//QAAAAQ=="
+            },
+            "proof": {}
+        }"#;
+        let mut rc = RevocationList2020Credential::from_str(data).unwrap();
+        assert_eq!(rc.id(), "https://example.com/credentials/status/3");
+        assert_eq!(
+            rc.list().id(),
+            "https://example.com/credentials/status/3#list"
+        );
+
+        let cr = serde_json::from_str::<SimpleCredential>(
+            r#"{
+            "issuer": "did:example:12345",
+            "credentialStatus": {
+                "id": "https://example.com/credentials/status/3#94567",
+                "type": "RevocationList2020Status",
+                "revocationListIndex": "94567",
+                "revocationListCredential": "https://example.com/credentials/status/3"
+            }
+        }"#,
+        )
+        .unwrap();
+        assert!(!rc.is_revoked(&cr).unwrap());
+        rc.revoke(&cr).unwrap();
+        assert!(rc.is_revoked(&cr).unwrap());
+
+        // FAIL: the issuer of a bare status entry is unknown,
+        // unless the policy allows it
+        let entry = &cr.entries()[0];
+        assert!(rc
+            .is_revoked(entry)
+            .unwrap_err()
+            .to_string()
+            .contains("the credential has no issuer"));
+        let mut policy = IssuerPolicy::new();
+        policy.allow_unknown_issuer();
+        assert!(rc.is_revoked_with_policy(entry, &policy).unwrap());
+
+        // the other fields of the credential are preserved
+        let out = serde_json::to_value(&rc).unwrap();
+        assert_eq!(out["issuer"], "did:example:12345");
+        assert_eq!(
+            out["@context"][1],
+            "https://w3id.org/vc-revocation-list-2020/v1"
+        );
+        let parsed = RevocationList2020Credential::from_str(&rc.to_string()).unwrap();
+        assert!(parsed.is_revoked(&cr).unwrap());
+
+        // FAIL: not a RevocationList2020Credential
+        let wrong = data.replace("\"RevocationList2020Credential\"", "\"Other\"");
+        assert!(RevocationList2020Credential::from_str(&wrong).is_err());
+    }
+
+    #[test]
+    fn test_bitstring() {
+        // FAIL: size out of range
+        assert!(Bitstring::new(15).is_err());
+        assert!(Bitstring::new(129).is_err());
+
+        let mut bs = Bitstring::new(16).unwrap();
+        assert_eq!(bs.capacity(), 16 * 1024 * 8);
+        bs.update(RevocationStatus::Revoke, 9).unwrap();
+        assert_eq!(bs.get(9).unwrap(), RevocationStatus::Revoke);
+        assert_eq!(bs.get(8).unwrap(), RevocationStatus::Reset);
+        assert_eq!(bs.as_bytes()[1], 0b10);
+        // FAIL: out of range
+        assert!(bs.get(16 * 1024 * 8).is_err());
+
+        // the encoding is the same of the revocation list
+        let mut rl = RevocationList2020::new("test-1", 16).unwrap();
+        rl.update(RevocationStatus::Revoke, 9).unwrap();
+        assert_eq!(bs.encode().unwrap(), rl.encoded_list);
+        assert_eq!(Bitstring::decode(&rl.encoded_list).unwrap(), bs);
+
+        bs.update(RevocationStatus::Revoke, 70000).unwrap();
+        assert_eq!(bs.revoked_count(), 2);
+        assert_eq!(bs.revoked_indexes().collect::<Vec<_>>(), vec![9, 70000]);
+
+        let mut other = Bitstring::new(16).unwrap();
+        other.update(RevocationStatus::Revoke, 9).unwrap();
+        other.update(RevocationStatus::Revoke, 10).unwrap();
+        let union = bs.union(&other).unwrap();
+        assert_eq!(
+            union.revoked_indexes().collect::<Vec<_>>(),
+            vec![9, 10, 70000]
+        );
+        let intersection = bs.intersection(&other).unwrap();
+        assert_eq!(intersection.revoked_indexes().collect::<Vec<_>>(), vec![9]);
+        // FAIL: sizes don't match
+        assert!(bs.union(&Bitstring::new(17).unwrap()).is_err());
+
+        // FAIL: not base64, not zlib
+        assert!(Bitstring::decode("not base64!").is_err());
+        assert!(Bitstring::decode("aGVsbG8=").is_err());
+        // FAIL: larger than the maximum size, a zip bomb is not inflated
+        let largest = Bitstring::new(128).unwrap();
+        assert_eq!(
+            Bitstring::decode(&largest.encode().unwrap()).unwrap(),
+            largest
+        );
+        let bomb = base64::encode(miniz_oxide::deflate::compress_to_vec_zlib(
+            &vec![0; 4 * 1024 * 1024],
+            6,
+        ));
+        assert!(Bitstring::decode(&bomb).is_err());
+    }
+
+    #[test]
+    fn test_bitstring_words() {
+        // the word level operations must match the byte level ones,
+        // also when the length is not a multiple of the word size
+        let mut r = rand::thread_rng();
+        for len in [0, 5, 8, 200, 1027] {
+            let a: Vec<u8> = (0..len).map(|_| r.gen::<u8>() & r.gen::<u8>()).collect();
+            let b: Vec<u8> = (0..len).map(|_| r.gen::<u8>() & r.gen::<u8>()).collect();
+
+            let ones = |bits: &[u8]| -> Vec<u64> {
+                (0..bits.len() as u64 * 8)
+                    .filter(|i| bits[(i / 8) as usize] & (1 << (i % 8)) != 0)
+                    .collect()
+            };
+            assert_eq!(bitstring::iter_ones(&a).collect::<Vec<_>>(), ones(&a));
+            assert_eq!(bitstring::count_ones(&a), ones(&a).len() as u64);
+
+            let or: Vec<u8> = a.iter().zip(&b).map(|(x, y)| x | y).collect();
+            let and: Vec<u8> = a.iter().zip(&b).map(|(x, y)| x & y).collect();
+            assert_eq!(bitstring::combine(&a, &b, BitOp::Or), or);
+            assert_eq!(bitstring::combine(&a, &b, BitOp::And), and);
+            // the same without popcnt and avx2
+            assert_eq!(
+                bitstring::count_ones_blocks(&a, |w| w.count_ones()),
+                ones(&a).len() as u64
+            );
+            let mut out = vec![0u8; len];
+            bitstring::combine_words(&a, &b, &mut out, BitOp::Or);
+            assert_eq!(out, or);
+
+            let (set, cleared) = bitstring::diff(&a, &b);
+            let (sa, sb) = (ones(&a), ones(&b));
+            assert_eq!(
+                set,
+                sb.iter()
+                    .filter(|i| !sa.contains(i))
+                    .copied()
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(
+                cleared,
+                sa.iter()
+                    .filter(|i| !sb.contains(i))
+                    .copied()
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_stream() {
+        let mut rl = RevocationList2020::new("test-1", 16).unwrap();
+        rl.update_many(RevocationStatus::Revoke, &[0, 9, 70000, 131071])
+            .unwrap();
+
+        // the streaming encoding is the same of the in memory one
+        let mut encoded = Vec::new();
+        let n = encode_stream(&mut rl.bit_set.as_slice(), &mut encoded).unwrap();
+        assert_eq!(n, 16 * 1024);
+        assert_eq!(String::from_utf8(encoded).unwrap(), rl.encoded_list);
+
+        let mut decoded = Vec::new();
+        let n = decode_stream(&mut rl.encoded_list.as_bytes(), &mut decoded).unwrap();
+        assert_eq!(n, 16 * 1024);
+        assert_eq!(decoded, rl.bit_set);
+
+        for i in [0, 1, 9, 10, 70000, 131071] {
+            let status = lookup_status(&mut rl.encoded_list.as_bytes(), i).unwrap();
+            assert_eq!(status, rl.get(i).unwrap());
+        }
+        // FAIL: out of range
+        assert!(lookup_status(&mut rl.encoded_list.as_bytes(), 131072).is_err());
+        // FAIL: not base64
+        assert!(lookup_status(&mut "not base64!".as_bytes(), 1).is_err());
+        assert!(decode_stream(&mut "not base64!".as_bytes(), &mut Vec::new()).is_err());
+        // FAIL: truncated zlib stream
+        let truncated = base64::encode(&base64::decode(&rl.encoded_list).unwrap()[..20]);
+        assert!(decode_stream(&mut truncated.as_bytes(), &mut Vec::new()).is_err());
+        // FAIL: the bitstring exceeds the maximum size
+        let mut bomb = Vec::new();
+        encode_stream(&mut vec![0u8; 129 * 1024].as_slice(), &mut bomb).unwrap();
+        let mut decoded = Vec::new();
+        assert!(decode_stream(&mut bomb.as_slice(), &mut decoded).is_err());
+        assert!(decoded.len() <= 128 * 1024 + 1);
+        // a bitstring of exactly the maximum size is accepted
+        let mut max = Vec::new();
+        encode_stream(&mut vec![0u8; 128 * 1024].as_slice(), &mut max).unwrap();
+        let n = decode_stream(&mut max.as_slice(), &mut Vec::new()).unwrap();
+        assert_eq!(n, 128 * 1024);
+
+        // the output doesn't depend on how the input is read, a reader
+        // that returns a few bytes at a time gives the same bytes
+        struct Trickle<'a>(&'a [u8]);
+        impl Read for Trickle<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let n = buf.len().min(self.0.len()).min(7);
+                buf[..n].copy_from_slice(&self.0[..n]);
+                self.0 = &self.0[n..];
+                Ok(n)
+            }
+        }
+        let mut r = rand::thread_rng();
+        let mut bs = Bitstring::new(128).unwrap();
+        for _ in 0..50_000 {
+            bs.update(
+                RevocationStatus::Revoke,
+                r.gen_range(0..bs.capacity() as u64),
+            )
+            .unwrap();
+        }
+        let mut encoded = Vec::new();
+        encode_stream(&mut Trickle(bs.as_bytes()), &mut encoded).unwrap();
+        assert_eq!(String::from_utf8(encoded).unwrap(), bs.encode().unwrap());
+        let mut decoded = Vec::new();
+        decode_stream(&mut Trickle(bs.encode().unwrap().as_bytes()), &mut decoded).unwrap();
+        assert_eq!(decoded, bs.as_bytes());
+    }
+
+    #[test]
+    fn test_compression() {
+        let mut rl = RevocationList2020::new("test-1", 16).unwrap();
+        let mut r = rand::thread_rng();
+        let indexes: Vec<u64> = (0..2000).map(|_| r.gen_range(0..20000)).collect();
+        rl.update_many(RevocationStatus::Revoke, &indexes).unwrap();
+
+        let stats = rl.compression_stats();
+        assert_eq!(stats.raw, 16 * 1024);
+        assert_eq!(stats.encoded, rl.encoded_list.len());
+        assert_eq!(
+            stats.compressed,
+            base64::decode(&rl.encoded_list).unwrap().len()
+        );
+        assert!(stats.ratio() < 1.0);
+
+        let mut sizes = Vec::new();
+        for compression in [
+            Compression::Level(0),
+            Compression::Level(1),
+            Compression::Default,
+            Compression::Level(10),
+            Compression::Best,
+        ] {
+            rl.set_compression(compression).unwrap();
+            assert_eq!(rl.compression(), compression);
+            // any compression decodes to the same list
+            let loaded = RevocationList2020::from_str(&rl.to_string()).unwrap();
+            assert_eq!(loaded.bit_set, rl.bit_set);
+            sizes.push(rl.compression_stats().compressed);
+        }
+        // no compression is the largest and best is the smallest
+        assert!(sizes[0] > 16 * 1024);
+        assert!(sizes.iter().all(|s| *s >= sizes[4]));
+
+        // the changes use the default compression, the best one
+        // is only applied when the list is encoded for publication
+        rl.update(RevocationStatus::Revoke, 30000).unwrap();
+        let bs = Bitstring::decode(&rl.encoded_list).unwrap();
+        assert_eq!(rl.encoded_list, bs.encode().unwrap());
+        rl.encode_for_publication().unwrap();
+        assert_eq!(rl.encoded_list, bs.encode_with(Compression::Best).unwrap());
+        rl.set_compression(Compression::Level(1)).unwrap();
+        rl.update(RevocationStatus::Revoke, 30001).unwrap();
+        let bs = Bitstring::decode(&rl.encoded_list).unwrap();
+        assert_eq!(
+            rl.encoded_list,
+            bs.encode_with(Compression::Level(1)).unwrap()
+        );
+        rl.set_compression(Compression::Best).unwrap();
+
+        // FAIL: invalid level, the list is unchanged
+        let encoded = rl.encoded_list.clone();
+        assert!(rl.set_compression(Compression::Level(11)).is_err());
+        assert_eq!(rl.compression(), Compression::Best);
+        assert_eq!(rl.encoded_list, encoded);
+    }
+
+    #[test]
+    fn test_deterministic_encoding() {
+        let mut rl = RevocationList2020::new("test-1", 16).unwrap();
+        rl.set_compression(Compression::Deterministic).unwrap();
+        assert_eq!(rl.encoded_list, "eAFjGAWjYBSMglEwCkbBKBgFo2AUjIJRMApGwSgYBaNgFIyCUTAKRsEoGAWjYBSMglEwCkbBKBgFo2AUjIJRMApGwSgYBaNgFIyCUTAKRsEoGAWjYBSMglEwCkbBKBgFo2AUjIJRMApGwYADAEAAAAE=");
+
+        // any run length and pattern decodes back with zlib
+        let mut r = rand::thread_rng();
+        let mut patterns: Vec<Vec<u8>> = vec![vec![], vec![7], vec![0xff; 16 * 1024]];
+        for run in [1, 2, 3, 4, 257, 258, 259, 260, 516, 517] {
+            let mut bits = vec![0u8; run];
+            bits.extend(vec![1u8; run + 1]);
+            patterns.push(bits);
+        }
+        patterns.push((0..16 * 1024).map(|_| r.gen()).collect());
+        patterns.push(
+            (0..16 * 1024)
+                .map(|_| r.gen::<u8>() & r.gen::<u8>() & r.gen::<u8>())
+                .collect(),
+        );
+        for bits in patterns {
+            let encoded = bitstring::encode_with(&bits, Compression::Deterministic).unwrap();
+            assert_eq!(bitstring::decode(&encoded).unwrap(), bits);
+            let mut decoded = Vec::new();
+            decode_stream(&mut encoded.as_bytes(), &mut decoded).unwrap();
+            assert_eq!(decoded, bits);
+        }
+
+        // re-encoding an unchanged list gives the same string
+        rl.update_many(RevocationStatus::Revoke, &[1, 2, 3, 5000])
+            .unwrap();
+        let loaded = RevocationList2020::from_str(&rl.to_string()).unwrap();
+        assert_eq!(
+            Bitstring::decode(&loaded.encoded_list)
+                .unwrap()
+                .encode_with(Compression::Deterministic)
+                .unwrap(),
+            rl.encoded_list
+        );
+    }
+
+    #[test]
+    fn test_canonical_json() {
+        let rl = RevocationList2020::new("test-1", 16).unwrap();
+        assert_eq!(
+            rl.to_canonical_json().unwrap(),
+            format!(
+                r#"{{"encodedList":"{}","id":"test-1","type":"RevocationList2020"}}"#,
+                rl.encoded_list
+            )
+        );
+
+        let data = format!(
+            r#"{{
+            "type": ["VerifiableCredential", "RevocationList2020Credential"],
+            "id": "https://example.com/status/1",
+            "issuer": {{"name": "Ünicode", "id": "did:example:1"}},
+            "credentialSubject": {},
+            "@context": ["https://www.w3.org/2018/credentials/v1"],
+            "issued": 1.0
+        }}"#,
+            rl
+        );
+        let rc = RevocationList2020Credential::from_str(&data).unwrap();
+        assert_eq!(
+            rc.to_canonical_json().unwrap(),
+            format!(
+                r#"{{"@context":["https://www.w3.org/2018/credentials/v1"],"credentialSubject":{},"id":"https://example.com/status/1","issued":1,"issuer":{{"id":"did:example:1","name":"Ünicode"}},"type":["VerifiableCredential","RevocationList2020Credential"]}}"#,
+                rl.to_canonical_json().unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn test_strict_validation() {
+        let mut rl = RevocationList2020::new("test-1", 16).unwrap();
+        rl.update(RevocationStatus::Revoke, 42).unwrap();
+        let valid = rl.encoded_list.clone();
+        let json = |encoded: &str| {
+            format!(
+                r#"{{"id": "test-1", "type": "RevocationList2020", "encodedList": "{}"}}"#,
+                encoded
+            )
+        };
+        let zlib =
+            |bits: &[u8]| base64::encode(miniz_oxide::deflate::compress_to_vec_zlib(bits, 6));
+        let error = |encoded: &str| {
+            RevocationList2020::from_str_strict(&json(encoded))
+                .unwrap_err()
+                .to_string()
+        };
+
+        // PASS: a canonical list
+        let loaded = RevocationList2020::from_str_strict(&json(&valid)).unwrap();
+        assert_eq!(loaded.bit_set, rl.bit_set);
+        assert!(loaded.validate().is_ok());
+
+        // FAIL: invalid base64 character
+        let encoded = format!("{}!{}", &valid[..10], &valid[11..]);
+        assert!(error(&encoded).contains("invalid base64 character '!' at offset 10"));
+        // FAIL: missing padding
+        let encoded = valid.trim_end_matches('=');
+        assert!(error(encoded).contains("base64"));
+        // FAIL: bits set after the data in the last character
+        let bin = base64::decode(&valid).unwrap();
+        assert_eq!(bin.len() % 3, 2);
+        let encoded = format!("{}F=", &valid[..valid.len() - 2]);
+        assert_ne!(encoded, valid);
+        assert!(error(&encoded).contains("non-canonical base64 character 'F' at offset"));
+        // FAIL: garbage after the zlib stream
+        let mut garbage = bin.clone();
+        garbage.extend_from_slice(b"garbage");
+        assert!(error(&base64::encode(&garbage)).contains(&format!(
+            "unexpected data after the end of the zlib stream at byte {}",
+            bin.len()
+        )));
+        // FAIL: truncated zlib stream
+        let encoded = base64::encode(&bin[..bin.len() / 2]);
+        assert!(error(&encoded).contains("truncated"));
+        // FAIL: bitstring too small or too large
+        assert!(error(&zlib(&[0; 1024])).contains("smaller than the minimum size"));
+        assert!(error(&zlib(&[0; 129 * 1024])).contains("exceeds the maximum size"));
+        // the lenient parsing accepts them
+        assert!(RevocationList2020::from_str(&json(&zlib(&[0; 1024]))).is_ok());
+
+        // FAIL: the encodedList doesn't match the bitset
+        let mut other = loaded.clone();
+        other.update(RevocationStatus::Revoke, 7).unwrap();
+        other.encoded_list = valid;
+        assert!(other
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("starting from index 7"));
+
+        // the credential subject is validated too
+        let credential = |list: String| {
+            format!(
+                r#"{{"id": "https://example.com/status/1", "type": ["VerifiableCredential", "RevocationList2020Credential"], "credentialSubject": {}}}"#,
+                list
+            )
+        };
+        assert!(RevocationList2020Credential::from_str_strict(&credential(rl.to_string())).is_ok());
+        let list = json(&base64::encode(&garbage));
+        assert!(RevocationList2020Credential::from_str(&credential(list.clone())).is_ok());
+        assert!(RevocationList2020Credential::from_str_strict(&credential(list)).is_err());
+    }
+
+    #[test]
+    fn test_jsonld() {
+        let loader = StaticLoader::default();
+        let data = r#"
+        {
+            "@context": [
+                "https://www.w3.org/2018/credentials/v1",
+                "https://w3id.org/vc-revocation-list-2020/v1"
+            ],
+            "id": "https://example.com/credentials/status/3",
+            "type": ["VerifiableCredential", "RevocationList2020Credential"],
+            "issuer": "did:example:12345",
+            "issuanceDate": "2020-04-05T14:27:42Z",
+            "credentialSubject": {
+                "id": "https://example.com/credentials/status/3#list",
+                "type": "RevocationList2020",
+                "encodedList": "eJzswDEBAAAAwiD7pzbGHhgAAAAAAAAAAAAAAAAAAACQewAAAP//QAAAAQ=="
+            }
+        }"#;
+        let mut rc = RevocationList2020Credential::from_str(data).unwrap();
+        assert!(rc.check_contexts().is_ok());
+
+        // FAIL: the RevocationList2020 context is missing
+        let wrong = data.replace(
+            ",\n                \"https://w3id.org/vc-revocation-list-2020/v1\"",
+            "",
+        );
+        let doc: Value = serde_json::from_str(&wrong).unwrap();
+        assert!(check_contexts(&doc)
+            .unwrap_err()
+            .to_string()
+            .contains("requires the https://w3id.org/vc-revocation-list-2020/v1 context"));
+        // FAIL: the credentials context must come first
+        let doc = json!({"@context": [REVOCATION_LIST_2020_CONTEXT, CREDENTIALS_V1_CONTEXT]});
+        assert!(check_contexts(&doc).is_err());
+        // FAIL: BitstringStatusList requires the v2 context
+        let doc = json!({
+            "@context": [CREDENTIALS_V1_CONTEXT],
+            "credentialStatus": {"type": "BitstringStatusListEntry"}
+        });
+        assert!(check_contexts(&doc).is_err());
+
+        // the terms are expanded to the IRIs of the contexts
+        let expanded = expand(&serde_json::from_str(data).unwrap(), &loader).unwrap();
+        let subject = &expanded[0]["https://www.w3.org/2018/credentials#credentialSubject"][0];
+        assert_eq!(
+            subject["@type"][0],
+            "https://w3id.org/vc-revocation-list-2020#RevocationList2020"
+        );
+        assert!(
+            subject["https://w3id.org/vc-revocation-list-2020#encodedList"][0]["@value"]
+                .is_string()
+        );
+        assert_eq!(
+            expanded[0]["https://www.w3.org/2018/credentials#issuanceDate"][0]["@type"],
+            "http://www.w3.org/2001/XMLSchema#dateTime"
+        );
+
+        // the status entries are identified by their IRIs, not by the literal terms
+        let vc = json!({
+            "@context": [
+                CREDENTIALS_V1_CONTEXT,
+                {"rl": "https://w3id.org/vc-revocation-list-2020#"}
+            ],
+            "type": ["VerifiableCredential"],
+            "issuer": "did:example:12345",
+            "credentialStatus": {
+                "id": "https://example.com/credentials/status/3#5",
+                "type": "rl:RevocationList2020Status",
+                "rl:revocationListIndex": "5",
+                "rl:revocationListCredential": {"id": "https://example.com/credentials/status/3"}
+            }
+        });
+        assert!(rc.revoke(&vc).is_err());
+        let expanded = ExpandedCredential::new(&vc, &loader).unwrap();
+        assert_eq!(expanded.entries()[0].index, 5);
+        rc.revoke(&expanded).unwrap();
+        assert!(rc.is_revoked(&expanded).unwrap());
+
+        // the StatusList2021 and VC v2 entries are recognized too
+        let vc = json!({
+            "@context": [CREDENTIALS_V2_CONTEXT],
+            "type": ["VerifiableCredential"],
+            "credentialStatus": [{
+                "id": "https://example.com/status/1#7",
+                "type": "BitstringStatusListEntry",
+                "statusPurpose": "suspension",
+                "statusListIndex": "7",
+                "statusListCredential": "https://example.com/status/1"
+            }, {
+                "@context": STATUS_LIST_2021_CONTEXT,
+                "type": "StatusList2021Entry",
+                "statusPurpose": "revocation",
+                "statusListIndex": "8",
+                "statusListCredential": "https://example.com/status/2"
+            }]
+        });
+        let expanded = ExpandedCredential::new(&vc, &loader).unwrap();
+        let entries = expanded.entries();
+        assert_eq!(entries[0].typ, "BitstringStatusListEntry");
+        assert_eq!(entries[0].purpose, StatusPurpose::Suspension);
+        assert_eq!(entries[1].typ, "StatusList2021Entry");
+        assert_eq!(entries[1].list, "https://example.com/status/2");
+        assert_eq!(entries[1].index, 8);
+
+        // the entries of unknown types are skipped
+        let vc = json!({
+            "@context": [CREDENTIALS_V2_CONTEXT, {"ex": "https://example.com/ns#"}],
+            "type": ["VerifiableCredential"],
+            "credentialStatus": [{
+                "id": "https://example.com/status/9#1",
+                "type": "ex:CustomStatusEntry"
+            }, {
+                "id": "https://example.com/status/1#7",
+                "type": "BitstringStatusListEntry",
+                "statusPurpose": "revocation",
+                "statusListIndex": "7",
+                "statusListCredential": "https://example.com/status/1"
+            }]
+        });
+        let expanded = ExpandedCredential::new(&vc, &loader).unwrap();
+        assert_eq!(expanded.entries().len(), 1);
+        assert_eq!(expanded.entries()[0].index, 7);
+        // FAIL: no entry of a supported type
+        let vc = json!({
+            "@context": [CREDENTIALS_V2_CONTEXT, {"ex": "https://example.com/ns#"}],
+            "type": ["VerifiableCredential"],
+            "credentialStatus": {"id": "https://example.com/status/9#1", "type": "ex:CustomStatusEntry"}
+        });
+        assert!(ExpandedCredential::new(&vc, &loader)
+            .unwrap_err()
+            .to_string()
+            .contains("no supported credentialStatus"));
+
+        // FAIL: the contexts are never fetched from the network
+        let vc = json!({"@context": ["https://example.com/unknown/v1"], "credentialStatus": {}});
+        assert!(ExpandedCredential::new(&vc, &loader)
+            .unwrap_err()
+            .to_string()
+            .contains("unknown JSON-LD context"));
+    }
+
+    #[test]
+    fn test_vc_versions() {
+        assert_eq!(datetime::format_rfc3339(1586096862), "2020-04-05T14:27:42Z");
+        for (date, ts) in [
+            ("2020-04-05T14:27:42Z", 1586096862),
+            ("2020-04-05T14:27:42.123Z", 1586096862),
+            ("2020-04-05T16:27:42+02:00", 1586096862),
+            ("2000-02-29T00:00:00Z", 951782400),
+        ] {
+            assert_eq!(datetime::parse_rfc3339(date).unwrap(), ts);
+        }
+        for date in [
+            "2020-04-05",
+            "2021-02-29T00:00:00Z",
+            "2020-04-05T14:27:42",
+            "1969-12-31T23:59:59Z",
+        ] {
+            assert!(datetime::parse_rfc3339(date).is_err());
+        }
+
+        let rl = RevocationList2020::new("https://example.com/status/1#list", 16).unwrap();
+        let mut rc = RevocationList2020Credential::new(
+            "https://example.com/status/1",
+            "did:example:12345",
+            rl,
+            VcVersion::V2,
+        )
+        .unwrap();
+        assert_eq!(rc.version(), VcVersion::V2);
+        assert!(rc.check_contexts().is_ok());
+        rc.set_validity(Some(1_000), Some(2_000));
+        let out = serde_json::to_value(&rc).unwrap();
+        assert_eq!(out["@context"][0], CREDENTIALS_V2_CONTEXT);
+        assert_eq!(out["validFrom"], "1970-01-01T00:16:40Z");
+        assert_eq!(out["validUntil"], "1970-01-01T00:33:20Z");
+        assert!(out.get("issuanceDate").is_none());
+
+        // the same list emitted as a VC 1.1 credential
+        rc.set_version(VcVersion::V1);
+        let out = serde_json::to_value(&rc).unwrap();
+        assert_eq!(out["@context"][0], CREDENTIALS_V1_CONTEXT);
+        assert_eq!(out["@context"][1], REVOCATION_LIST_2020_CONTEXT);
+        assert_eq!(out["issuanceDate"], "1970-01-01T00:16:40Z");
+        assert_eq!(out["expirationDate"], "1970-01-01T00:33:20Z");
+        assert!(out.get("validFrom").is_none());
+        let parsed = RevocationList2020Credential::from_str(&rc.to_string()).unwrap();
+        assert_eq!(parsed.version(), VcVersion::V1);
+        assert_eq!(parsed.valid_from().unwrap(), Some(1_000));
+        assert_eq!(parsed.valid_until().unwrap(), Some(2_000));
+
+        // the checker rejects the credential outside of its validity period
+        let cr = serde_json::from_str::<SimpleCredential>(
+            r#"{
+            "issuer": "did:example:12345",
+            "credentialStatus": {
+                "id": "https://example.com/status/1#9",
+                "type": "RevocationList2020Status",
+                "revocationListIndex": "9",
+                "revocationListCredential": "https://example.com/status/1"
+            }
+        }"#,
+        )
+        .unwrap();
+        let clock = TestClock(Cell::new(1_500));
+        let mut checker = StatusChecker::with_clock(&clock);
+        checker.add_credential(&parsed);
+        assert!(checker.check(&cr).unwrap().is_valid());
+        // FAIL: not yet valid
+        clock.0.set(999);
+        assert!(checker
+            .check(&cr)
+            .unwrap_err()
+            .to_string()
+            .contains("is not valid before 1970-01-01T00:16:40Z"));
+        // FAIL: expired
+        clock.0.set(2_001);
+        assert!(checker
+            .check(&cr)
+            .unwrap_err()
+            .to_string()
+            .contains("expired at 1970-01-01T00:33:20Z"));
+
+        // FAIL: is_revoked checks the validity at the current time
+        assert!(parsed
+            .is_revoked(&cr)
+            .unwrap_err()
+            .to_string()
+            .contains("expired at 1970-01-01T00:33:20Z"));
+        // is_revoked_at checks the validity at the time of the clock
+        clock.0.set(1_500);
+        let policy = IssuerPolicy::default();
+        assert!(!parsed.is_revoked_at(&cr, &policy, &clock).unwrap());
+        clock.0.set(2_001);
+        assert!(parsed.is_revoked_at(&cr, &policy, &clock).is_err());
+        let mut current = parsed.clone();
+        current.set_validity(Some(1_000), None);
+        assert!(!current.is_revoked(&cr).unwrap());
+
+        // FAIL: the date is malformed
+        let mut wrong = serde_json::to_value(&rc).unwrap();
+        wrong["expirationDate"] = json!("tomorrow");
+        let wrong = RevocationList2020Credential::from_str(&wrong.to_string()).unwrap();
+        assert!(wrong.check_validity(1_500).is_err());
+    }
+
+    #[test]
+    fn test_issuer_binding() {
+        let rl = RevocationList2020::new("https://example.com/status/1#list", 16).unwrap();
+        let rc = RevocationList2020Credential::new(
+            "https://example.com/status/1",
+            "did:example:issuer",
+            rl,
+            VcVersion::V2,
+        )
+        .unwrap();
+        assert_eq!(rc.issuer().as_deref(), Some("did:example:issuer"));
+        let vc = |issuer: Value| {
+            json!({
+                "issuer": issuer,
+                "credentialStatus": {
+                    "id": "https://example.com/status/1#9",
+                    "type": "RevocationList2020Status",
+                    "revocationListIndex": "9",
+                    "revocationListCredential": "https://example.com/status/1"
+                }
+            })
+        };
+
+        // PASS: the same issuer, also as an object
+        assert!(!rc.is_revoked(&vc(json!("did:example:issuer"))).unwrap());
+        let same = vc(json!({"id": "did:example:issuer", "name": "Issuer"}));
+        assert!(!rc.is_revoked(&same).unwrap());
+        let simple = serde_json::from_value::<SimpleCredential>(same).unwrap();
+        assert!(!rc.is_revoked(&simple).unwrap());
+
+        // FAIL: the list is published by another issuer
+        let other = vc(json!("did:example:other"));
+        assert!(rc
+            .is_revoked(&other)
+            .unwrap_err()
+            .to_string()
+            .contains("the status list is issued by did:example:issuer"));
+        let simple = serde_json::from_value::<SimpleCredential>(other.clone()).unwrap();
+        assert!(rc.is_revoked(&simple).is_err());
+        let mut checker = StatusChecker::new();
+        checker.add_credential(&rc);
+        assert!(checker.check(&other).is_err());
+
+        // PASS: the issuer delegates its status lists
+        let mut policy = IssuerPolicy::new();
+        policy.allow_delegate("did:example:other", "did:example:issuer");
+        assert!(rc.check_issuer(&other, &policy).is_ok());
+        checker.set_issuer_policy(policy);
+        assert!(checker.check(&other).unwrap().is_valid());
+        // FAIL: the delegation is not mutual
+        assert!(checker.check(&vc(json!("did:example:third"))).is_err());
+
+        // FAIL: the list credential has no issuer
+        let mut anonymous = serde_json::to_value(&rc).unwrap();
+        anonymous.as_object_mut().unwrap().remove("issuer");
+        let anonymous = RevocationList2020Credential::from_str(&anonymous.to_string()).unwrap();
+        assert!(anonymous
+            .is_revoked(&vc(json!("did:example:issuer")))
+            .unwrap_err()
+            .to_string()
+            .contains("has no issuer"));
+    }
+
+    #[test]
+    #[cfg(feature = "proof")]
+    fn test_did_proof() {
+        use crate::{
+            resolve_verification_method, Cryptosuite, DidKeyResolver, DidResolver, DidResolvers,
+            DidWebResolver, DirFetcher, MemorySigner, PrivateKey, PublicKey, StatusEntry,
+        };
+
+        let secret = [7u8; 32];
+        let key = PrivateKey::Ed25519(secret);
+        let public = ed25519_dalek::SigningKey::from_bytes(&secret)
+            .verifying_key()
+            .to_bytes();
+        let did = DidKeyResolver::did(&PublicKey::Ed25519(public));
+        assert!(did.starts_with("did:key:z6Mk"));
+        let vm = format!("{}#{}", did, &did[8..]);
+
+        // did:key of a P-256 key
+        let p256_key = p256::SecretKey::from_slice(&[1u8; 32])
+            .unwrap()
+            .public_key();
+        let compressed = p256::EncodedPoint::from(p256_key).compress();
+        let p256_did = DidKeyResolver::did(&PublicKey::P256(compressed.as_bytes().to_vec()));
+        assert!(p256_did.starts_with("did:key:zDn"));
+        let method = resolve_verification_method(
+            &DidKeyResolver,
+            &format!("{}#{}", p256_did, &p256_did[8..]),
+            "assertionMethod",
+        )
+        .unwrap();
+        assert_eq!(method.controller, p256_did);
+        assert_eq!(method.key, PublicKey::P256(compressed.as_bytes().to_vec()));
+        // FAIL: the method is not in the DID document
+        let wrong = format!("{}#other", did);
+        assert!(resolve_verification_method(&DidKeyResolver, &wrong, "assertionMethod").is_err());
+
+        // sign and verify a list issued by a did:key
+        let resolvers = DidResolvers::new();
+        let rl = RevocationList2020::new("https://example.com/status/1#list", 16).unwrap();
+        let mut rc = RevocationList2020Credential::new(
+            "https://example.com/status/1",
+            &did,
+            rl,
+            VcVersion::V2,
+        )
+        .unwrap();
+        assert!(rc.verify_proof(&resolvers).is_err());
+        rc.sign(
+            &MemorySigner::new(key.clone(), &vm),
+            Cryptosuite::EddsaJcs2022,
+        )
+        .unwrap();
+        rc.verify_proof(&resolvers).unwrap();
+        let parsed = RevocationList2020Credential::from_str(&rc.to_string()).unwrap();
+        parsed.verify_proof(&resolvers).unwrap();
+
+        // FAIL: the list changed after signing
+        let status = StatusEntry {
+            id: String::new(),
+            typ: REVOCATION_LIST_2020_STATUS_TYPE.to_owned(),
+            purpose: StatusPurpose::Revocation,
+            list: "https://example.com/status/1".to_owned(),
+            index: 3,
+        };
+        let mut tampered = parsed.clone();
+        tampered.revoke(&status).unwrap();
+        assert!(tampered
+            .verify_proof(&resolvers)
+            .unwrap_err()
+            .to_string()
+            .contains("invalid proof signature"));
+        // FAIL: the proof is not signed by the issuer
+        let mut other = serde_json::to_value(&parsed).unwrap();
+        other["issuer"] = json!("did:example:other");
+        let other = RevocationList2020Credential::from_str(&other.to_string()).unwrap();
+        assert!(other.verify_proof(&resolvers).is_err());
+        // FAIL: P-256 keys cannot sign eddsa proofs
+        let mut wrong_key = serde_json::to_value(&parsed).unwrap();
+        wrong_key["issuer"] = json!(p256_did);
+        wrong_key["proof"]["verificationMethod"] =
+            json!(format!("{}#{}", p256_did, &p256_did[8..]));
+        let wrong_key = RevocationList2020Credential::from_str(&wrong_key.to_string()).unwrap();
+        assert!(wrong_key
+            .verify_proof(&resolvers)
+            .unwrap_err()
+            .to_string()
+            .contains("cannot verify"));
 
-#[wasm_bindgen]
-pub fn reset_credential(
-    revocation_list_credential: &str,
-    subject_credential: &str,
-) -> Result<String, JsValue> {
-    let mut rl = RevocationList2020::from_str(revocation_list_credential).map_err(|err| {
-        JsValue::from(&format!(
-            "error parsing the revocation list: {}",
-            err.to_string()
-        ))
-    })?;
-    let cr = serde_json::from_str::<SimpleCredential>(subject_credential).map_err(|err| {
-        JsValue::from(&format!(
-            "error parsing the input credential: {}",
-            err.to_string()
-        ))
-    })?;
-    rl.reset(&cr).map_err(|err| {
-        JsValue::from(&format!(
-            "error checking the revocation status: {}",
-            err.to_string()
-        ))
-    })?;
-    Ok(rl.to_string())
+        // the checker verifies the proofs of the list credentials
+        let vc = json!({
+            "issuer": did,
+            "credentialStatus": {
+                "id": "https://example.com/status/1#3",
+                "type": "RevocationList2020Status",
+                "revocationListIndex": "3",
+                "revocationListCredential": "https://example.com/status/1"
+            }
+        });
+        let mut checker = StatusChecker::new();
+        checker.require_proof();
+        checker
+            .add_verified_credential(&parsed, &resolvers)
+            .unwrap();
+        assert!(checker.check(&vc).unwrap().is_valid());
+        // FAIL: the proof doesn't match the list
+        assert!(checker
+            .add_verified_credential(&tampered, &resolvers)
+            .is_err());
+        assert!(checker.check(&vc).unwrap().is_valid());
+        // FAIL: the list credential was added without verifying its proof
+        checker.add_credential(&tampered);
+        assert!(checker
+            .check(&vc)
+            .unwrap_err()
+            .to_string()
+            .contains("has not been verified"));
+
+        // did:web documents are fetched from a local directory
+        let root = std::env::temp_dir().join(format!("rl2020-did-web-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("example.com:8443/issuers/1")).unwrap();
+        let web_did = "did:web:example.com%3A8443:issuers:1";
+        let document = json!({
+            "@context": ["https://www.w3.org/ns/did/v1"],
+            "id": web_did,
+            "verificationMethod": [{
+                "id": "#key-1",
+                "type": "JsonWebKey2020",
+                "controller": web_did,
+                "publicKeyJwk": {
+                    "kty": "OKP",
+                    "crv": "Ed25519",
+                    "x": base64::encode_config(public, base64::URL_SAFE_NO_PAD)
+                }
+            }],
+            "assertionMethod": ["#key-1"]
+        });
+        std::fs::write(
+            root.join("example.com:8443/issuers/1/did.json"),
+            document.to_string(),
+        )
+        .unwrap();
+        let web = DidWebResolver::new(DirFetcher::new(&root));
+        assert_eq!(web.resolve(web_did).unwrap()["id"], web_did);
+        let mut resolvers = DidResolvers::new();
+        resolvers.add("web", web);
+        let rl = RevocationList2020::new("https://example.com/status/2#list", 16).unwrap();
+        let mut rc = RevocationList2020Credential::new(
+            "https://example.com/status/2",
+            web_did,
+            rl,
+            VcVersion::V1,
+        )
+        .unwrap();
+        rc.sign(
+            &MemorySigner::new(key, &format!("{}#key-1", web_did)),
+            Cryptosuite::EddsaJcs2022,
+        )
+        .unwrap();
+        rc.verify_proof(&resolvers).unwrap();
+        // FAIL: unknown did:web
+        let unknown = "did:web:example.org";
+        assert!(resolvers.resolve(unknown).is_err());
+        // FAIL: unsupported DID method
+        assert!(resolvers
+            .resolve("did:example:1")
+            .unwrap_err()
+            .to_string()
+            .contains("unsupported DID method"));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "proof")]
+    fn test_ecdsa_proof() {
+        use crate::{
+            Cryptosuite, DidKeyResolver, DidResolvers, MemorySigner, PrivateKey, Signer,
+            StatusEntry,
+        };
+
+        let resolvers = DidResolvers::new();
+        let status = StatusEntry {
+            id: String::new(),
+            typ: REVOCATION_LIST_2020_STATUS_TYPE.to_owned(),
+            purpose: StatusPurpose::Revocation,
+            list: "https://example.com/status/1".to_owned(),
+            index: 3,
+        };
+        let keys = [
+            PrivateKey::P256([1u8; 32]),
+            PrivateKey::P384([2u8; 48]),
+            PrivateKey::Ed25519([3u8; 32]),
+        ];
+        for key in &keys {
+            let did = DidKeyResolver::did(&key.public_key().unwrap());
+            let signer = MemorySigner::new(key.clone(), &format!("{}#{}", did, &did[8..]));
+            for version in [VcVersion::V1, VcVersion::V2] {
+                let rl = RevocationList2020::new("https://example.com/status/1#list", 16).unwrap();
+                let mut rc = RevocationList2020Credential::new(
+                    "https://example.com/status/1",
+                    &did,
+                    rl,
+                    version,
+                )
+                .unwrap();
+                let suites = match (key, version) {
+                    (PrivateKey::Ed25519(_), _) => vec![Cryptosuite::EddsaJcs2022],
+                    (_, VcVersion::V1) => {
+                        vec![Cryptosuite::EcdsaJcs2019, Cryptosuite::EcdsaRdfc2019]
+                    }
+                    // FAIL: the VC 2.0 context protects the description redefined
+                    // by the RevocationList2020 context, so it can't be expanded
+                    (_, VcVersion::V2) => {
+                        assert!(rc
+                            .sign(&signer, Cryptosuite::EcdsaRdfc2019)
+                            .unwrap_err()
+                            .to_string()
+                            .contains("protected JSON-LD term description"));
+                        vec![Cryptosuite::EcdsaJcs2019]
+                    }
+                };
+                for suite in suites {
+                    rc.sign(&signer, suite).unwrap();
+                    assert_eq!(
+                        serde_json::to_value(&rc).unwrap()["proof"]["cryptosuite"],
+                        suite.name()
+                    );
+                    let parsed = RevocationList2020Credential::from_str(&rc.to_string()).unwrap();
+                    parsed.verify_proof(&resolvers).unwrap();
+                    parsed.check_contexts().unwrap();
+                    // FAIL: the list changed after signing
+                    let mut tampered = parsed.clone();
+                    tampered.revoke(&status).unwrap();
+                    assert!(tampered.verify_proof(&resolvers).is_err());
+                }
+                // FAIL: the cryptosuite doesn't sign with the key
+                let wrong = match key {
+                    PrivateKey::Ed25519(_) => Cryptosuite::EcdsaRdfc2019,
+                    _ => Cryptosuite::EddsaJcs2022,
+                };
+                assert!(rc.sign(&signer, wrong).is_err());
+
+                // the JWT encodings
+                let jwt = rc.to_jwt(&signer).unwrap();
+                let header: Value = serde_json::from_slice(
+                    &base64::decode_config(jwt.split('.').next().unwrap(), base64::URL_SAFE_NO_PAD)
+                        .unwrap(),
+                )
+                .unwrap();
+                assert_eq!(header["alg"], signer.algorithm().name());
+                assert_eq!(header["kid"], signer.key_id());
+                let decoded = RevocationList2020Credential::from_jwt(&jwt, &resolvers).unwrap();
+                assert_eq!(decoded.id(), rc.id());
+                assert_eq!(decoded.issuer(), rc.issuer());
+                assert_eq!(decoded.valid_from().unwrap(), rc.valid_from().unwrap());
+                assert_eq!(decoded.version(), version);
+                // FAIL: tampered signature
+                let mut tampered = jwt.clone();
+                let last = if tampered.ends_with('A') { "B" } else { "A" };
+                tampered.replace_range(tampered.len() - 1.., last);
+                assert!(RevocationList2020Credential::from_jwt(&tampered, &resolvers).is_err());
+                // FAIL: unsigned JWT
+                let parts: Vec<&str> = jwt.split('.').collect();
+                let none = base64::encode_config(r#"{"alg":"none"}"#, base64::URL_SAFE_NO_PAD);
+                let unsigned = format!("{}.{}.", none, parts[1]);
+                assert!(RevocationList2020Credential::from_jwt(&unsigned, &resolvers).is_err());
+                // FAIL: the vc claim is not an object
+                for vc in ["1", "[]", "\"vc\""] {
+                    let claims = format!(r#"{{"iss":"{}","vc":{}}}"#, did, vc);
+                    let input = format!(
+                        "{}.{}",
+                        parts[0],
+                        base64::encode_config(claims, base64::URL_SAFE_NO_PAD)
+                    );
+                    let signature = signer.sign(input.as_bytes()).unwrap();
+                    let forged = format!(
+                        "{}.{}",
+                        input,
+                        base64::encode_config(signature, base64::URL_SAFE_NO_PAD)
+                    );
+                    assert!(RevocationList2020Credential::from_jwt(&forged, &resolvers)
+                        .unwrap_err()
+                        .to_string()
+                        .contains("the vc claim is not an object"));
+                }
+            }
+        }
+        // FAIL: the JWT algorithm doesn't match the key
+        let p256_did = DidKeyResolver::did(&keys[0].public_key().unwrap());
+        let rl = RevocationList2020::new("https://example.com/status/1#list", 16).unwrap();
+        let rc = RevocationList2020Credential::new(
+            "https://example.com/status/1",
+            &p256_did,
+            rl,
+            VcVersion::V2,
+        )
+        .unwrap();
+        let jwt = rc
+            .to_jwt(&MemorySigner::new(
+                keys[1].clone(),
+                &format!("{}#{}", p256_did, &p256_did[8..]),
+            ))
+            .unwrap();
+        assert!(RevocationList2020Credential::from_jwt(&jwt, &resolvers)
+            .unwrap_err()
+            .to_string()
+            .contains("cannot verify ES384"));
+    }
+
+    #[test]
+    #[cfg(feature = "proof")]
+    fn test_pkcs11_signer() {
+        use crate::{
+            Algorithm, CredentialError, Cryptosuite, DidKeyResolver, DidResolvers, Pkcs11Mechanism,
+            Pkcs11Session, Pkcs11Signer, PrivateKey,
+        };
+        use p256::ecdsa::signature::hazmat::PrehashSigner;
+
+        // a token that holds its keys behind object handles, like SoftHSM
+        struct Token {
+            keys: Vec<PrivateKey>,
+        }
+
+        impl Pkcs11Session for &Token {
+            type Key = usize;
+
+            fn sign(
+                &self,
+                mechanism: Pkcs11Mechanism,
+                key: &usize,
+                data: &[u8],
+            ) -> Result<Vec<u8>, CredentialError> {
+                let key = self
+                    .keys
+                    .get(*key)
+                    .ok_or_else(|| CredentialError::new("CKR_KEY_HANDLE_INVALID"))?;
+                let invalid = |e: &dyn std::fmt::Display| CredentialError::new(&e.to_string());
+                match (mechanism, key) {
+                    (Pkcs11Mechanism::Eddsa, PrivateKey::Ed25519(secret)) => {
+                        use ed25519_dalek::Signer;
+                        Ok(ed25519_dalek::SigningKey::from_bytes(secret)
+                            .sign(data)
+                            .to_vec())
+                    }
+                    (Pkcs11Mechanism::Ecdsa, PrivateKey::P256(secret)) => {
+                        let key = p256::ecdsa::SigningKey::from_slice(secret).unwrap();
+                        let signature: p256::ecdsa::Signature =
+                            key.sign_prehash(data).map_err(|e| invalid(&e))?;
+                        Ok(signature.to_vec())
+                    }
+                    (Pkcs11Mechanism::Ecdsa, PrivateKey::P384(secret)) => {
+                        let key = p384::ecdsa::SigningKey::from_slice(secret).unwrap();
+                        let signature: p384::ecdsa::Signature =
+                            key.sign_prehash(data).map_err(|e| invalid(&e))?;
+                        Ok(signature.to_vec())
+                    }
+                    _ => Err(CredentialError::new("CKR_KEY_TYPE_INCONSISTENT")),
+                }
+            }
+        }
+
+        let token = Token {
+            keys: vec![
+                PrivateKey::Ed25519([4u8; 32]),
+                PrivateKey::P256([5u8; 32]),
+                PrivateKey::P384([6u8; 48]),
+            ],
+        };
+        let resolvers = DidResolvers::new();
+        let suites = [
+            (Algorithm::EdDSA, Cryptosuite::EddsaJcs2022),
+            (Algorithm::ES256, Cryptosuite::EcdsaRdfc2019),
+            (Algorithm::ES384, Cryptosuite::EcdsaJcs2019),
+        ];
+        for (handle, (algorithm, suite)) in suites.into_iter().enumerate() {
+            // the public key is known to the issuer, the private key stays in the token
+            let did = DidKeyResolver::did(&token.keys[handle].public_key().unwrap());
+            let kid = format!("{}#{}", did, &did[8..]);
+            let signer = Pkcs11Signer::new(&token, handle, &kid, algorithm);
+            let rl = RevocationList2020::new("https://example.com/status/1#list", 16).unwrap();
+            let mut rc = RevocationList2020Credential::new(
+                "https://example.com/status/1",
+                &did,
+                rl,
+                VcVersion::V1,
+            )
+            .unwrap();
+            rc.sign(&signer, suite).unwrap();
+            rc.verify_proof(&resolvers).unwrap();
+            let jwt = rc.to_jwt(&signer).unwrap();
+            RevocationList2020Credential::from_jwt(&jwt, &resolvers).unwrap();
+            // FAIL: the object is not a key of the algorithm
+            let wrong = Pkcs11Signer::new(&token, (handle + 1) % 3, &kid, algorithm);
+            assert!(rc.sign(&wrong, suite).is_err());
+        }
+        // FAIL: the token returns a signature that isn't r and s
+        struct DerToken;
+        impl Pkcs11Session for DerToken {
+            type Key = ();
+
+            fn sign(
+                &self,
+                _: Pkcs11Mechanism,
+                _: &(),
+                _: &[u8],
+            ) -> Result<Vec<u8>, CredentialError> {
+                Ok(vec![0x30; 70])
+            }
+        }
+        let signer = Pkcs11Signer::new(DerToken, (), "did:example:1#key-1", Algorithm::ES256);
+        let rl = RevocationList2020::new("https://example.com/status/1#list", 16).unwrap();
+        let mut rc = RevocationList2020Credential::new(
+            "https://example.com/status/1",
+            "did:example:1",
+            rl,
+            VcVersion::V2,
+        )
+        .unwrap();
+        assert!(rc
+            .sign(&signer, Cryptosuite::EcdsaJcs2019)
+            .unwrap_err()
+            .to_string()
+            .contains("70 bytes"));
+    }
+
+    #[test]
+    #[ignore]
+    #[cfg(feature = "pkcs11")]
+    fn test_pkcs11_softhsm() {
+        use crate::{
+            Algorithm, Cryptosuite, DidKeyResolver, DidResolvers, Pkcs11Module, Pkcs11Signer,
+        };
+
+        // a SoftHSM token labeled rl2020 with the pin 1234 and a key pair labeled
+        // rl2020, see the README, the module is the path of libsofthsm2.so
+        let (Ok(_), Ok(module)) = (
+            std::env::var("SOFTHSM2_CONF"),
+            std::env::var("RL2020_PKCS11_MODULE"),
+        ) else {
+            panic!("SOFTHSM2_CONF and RL2020_PKCS11_MODULE must be set to run against SoftHSM");
+        };
+        let module = Pkcs11Module::load(module).unwrap();
+        let session = module
+            .login(module.slot("rl2020").unwrap(), "1234")
+            .unwrap();
+        let key = session.private_key("rl2020").unwrap();
+        let public = session.public_key("rl2020").unwrap();
+        let did = DidKeyResolver::did(&public);
+        let algorithm = Algorithm::of(&public);
+        let suite = match algorithm {
+            Algorithm::EdDSA => Cryptosuite::EddsaJcs2022,
+            _ => Cryptosuite::EcdsaJcs2019,
+        };
+        let signer = Pkcs11Signer::new(session, key, &format!("{}#{}", did, &did[8..]), algorithm);
+        let rl = RevocationList2020::new("https://example.com/status/1#list", 16).unwrap();
+        let mut rc = RevocationList2020Credential::new(
+            "https://example.com/status/1",
+            &did,
+            rl,
+            VcVersion::V2,
+        )
+        .unwrap();
+        rc.sign(&signer, suite).unwrap();
+        rc.verify_proof(&DidResolvers::new()).unwrap();
+        // FAIL: no key with the label
+        assert!(module
+            .login(module.slot("rl2020").unwrap(), "1234")
+            .unwrap()
+            .private_key("missing")
+            .is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "proof")]
+    fn test_rdfc() {
+        let canonicalize = |doc: Value| {
+            crate::rdfc::canonicalize(&expand(&doc, &StaticLoader::default()).unwrap()).unwrap()
+        };
+        let ctx = json!({"@vocab": "http://example.com/"});
+        // the blank nodes are labeled by their quads, not by their order
+        let doc = json!({
+            "@context": ctx,
+            "@id": "http://example.com/a",
+            "p": {"q": "v", "n": 1, "d": 1.5, "b": true, "s": "line\n\"quoted\""}
+        });
+        assert_eq!(
+            canonicalize(doc),
+            concat!(
+                "<http://example.com/a> <http://example.com/p> _:c14n0 .\n",
+                "_:c14n0 <http://example.com/b> \"true\"^^<http://www.w3.org/2001/XMLSchema#boolean> .\n",
+                "_:c14n0 <http://example.com/d> \"1.5E0\"^^<http://www.w3.org/2001/XMLSchema#double> .\n",
+                "_:c14n0 <http://example.com/n> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n",
+                "_:c14n0 <http://example.com/q> \"v\" .\n",
+                "_:c14n0 <http://example.com/s> \"line\\n\\\"quoted\\\"\" .\n",
+            )
+        );
+        // a cycle of blank nodes that only the N-degree hashes tell apart
+        let cycle = |labels: [&str; 3]| {
+            json!({
+                "@context": ctx,
+                "@graph": [
+                    {"@id": labels[0], "p": {"@id": labels[1]}},
+                    {"@id": labels[1], "p": {"@id": labels[2]}},
+                    {"@id": labels[2], "p": {"@id": labels[0]}, "q": "end"}
+                ]
+            })
+        };
+        let canonical = canonicalize(cycle(["_:x", "_:y", "_:z"]));
+        assert_eq!(canonical, canonicalize(cycle(["_:b", "_:a", "_:c"])));
+        assert_eq!(canonical.lines().count(), 4);
+        assert!(canonical.contains("<http://example.com/q> \"end\" ."));
+        // FAIL: relative IRIs are not signed
+        assert!(crate::rdfc::canonicalize(
+            &json!([{"@id": "relative", "http://example.com/p": [{"@value": 1}]}])
+        )
+        .is_err());
+        // FAIL: the included and reverse nodes would not be signed
+        for keyword in ["@included", "@reverse"] {
+            let doc = json!([{
+                "@id": "http://example.com/a",
+                keyword: [{"@id": "http://example.com/b", "http://example.com/p": [{"@value": 1}]}]
+            }]);
+            assert!(crate::rdfc::canonicalize(&doc)
+                .unwrap_err()
+                .to_string()
+                .contains(keyword));
+        }
+
+        // the unique hashes and shared hashes examples of https://www.w3.org/TR/rdf-canon/,
+        // in the second the first degree hashes of e0 and e1 and of e2 and e3 are the same
+        let node = |id: &str, properties: Value| {
+            let mut node = properties;
+            node["@id"] = json!(id);
+            node
+        };
+        let unique = json!([
+            node(
+                "http://example.com/#p",
+                json!({
+                    "http://example.com/#q": [{"@id": "_:e0"}],
+                    "http://example.com/#r": [{"@id": "_:e1"}]
+                })
+            ),
+            node(
+                "_:e0",
+                json!({"http://example.com/#s": [{"@id": "http://example.com/#u"}]})
+            ),
+            node(
+                "_:e1",
+                json!({"http://example.com/#t": [{"@id": "http://example.com/#u"}]})
+            ),
+        ]);
+        assert_eq!(
+            crate::rdfc::canonicalize(&unique).unwrap(),
+            concat!(
+                "<http://example.com/#p> <http://example.com/#q> _:c14n0 .\n",
+                "<http://example.com/#p> <http://example.com/#r> _:c14n1 .\n",
+                "_:c14n0 <http://example.com/#s> <http://example.com/#u> .\n",
+                "_:c14n1 <http://example.com/#t> <http://example.com/#u> .\n",
+            )
+        );
+        let shared = json!([
+            node(
+                "http://example.com/#p",
+                json!({"http://example.com/#q": [{"@id": "_:e0"}, {"@id": "_:e1"}]})
+            ),
+            node("_:e0", json!({"http://example.com/#p": [{"@id": "_:e2"}]})),
+            node("_:e1", json!({"http://example.com/#p": [{"@id": "_:e3"}]})),
+            node("_:e2", json!({"http://example.com/#r": [{"@id": "_:e3"}]})),
+        ]);
+        assert_eq!(
+            crate::rdfc::canonicalize(&shared).unwrap(),
+            concat!(
+                "<http://example.com/#p> <http://example.com/#q> _:c14n2 .\n",
+                "<http://example.com/#p> <http://example.com/#q> _:c14n3 .\n",
+                "_:c14n0 <http://example.com/#r> _:c14n1 .\n",
+                "_:c14n2 <http://example.com/#p> _:c14n1 .\n",
+                "_:c14n3 <http://example.com/#p> _:c14n0 .\n",
+            )
+        );
+
+        // the strict expansion signs only the constructs of the bundled contexts
+        let strict = |local: Value, properties: Value| {
+            let mut doc = json!({
+                "@context": [CREDENTIALS_V1_CONTEXT, local],
+                "id": "https://example.com/credentials/1",
+                "type": ["VerifiableCredential"],
+                "issuer": "did:example:12345"
+            });
+            doc.as_object_mut()
+                .unwrap()
+                .extend(properties.as_object().unwrap().clone());
+            crate::jsonld::expand_strict(&doc, &StaticLoader::default())
+        };
+        let vocab = json!({"@vocab": "https://example.com/#"});
+        assert!(strict(vocab.clone(), json!({"p": "v"})).is_ok());
+        // a protected term can be redefined in the same way
+        assert!(strict(json!({"id": "@id"}), json!({})).is_ok());
+        for (local, properties, error) in [
+            (
+                json!({"@base": "https://example.com/"}),
+                json!({}),
+                "keyword @base",
+            ),
+            (json!({"@language": "en"}), json!({}), "keyword @language"),
+            (
+                json!({"@import": "https://example.com/ctx"}),
+                json!({}),
+                "keyword @import",
+            ),
+            (json!({"@propagate": true}), json!({}), "keyword @propagate"),
+            (
+                json!({"@vocab": "relative/"}),
+                json!({}),
+                "not an absolute IRI",
+            ),
+            (
+                json!({"p": {"@reverse": "https://example.com/#p"}}),
+                json!({}),
+                "@reverse of the JSON-LD term p",
+            ),
+            (
+                json!({"p": {"@id": "https://example.com/#p", "@container": "@index"}}),
+                json!({}),
+                "@container of the JSON-LD term p",
+            ),
+            (
+                json!({"p": {"@id": "https://example.com/#p", "@language": "en"}}),
+                json!({}),
+                "@language of the JSON-LD term p",
+            ),
+            (
+                vocab.clone(),
+                json!({"p": {"@value": "v", "@language": "en"}}),
+                "keyword @",
+            ),
+            (
+                vocab.clone(),
+                json!({"@included": [{"p": "v"}]}),
+                "keyword @included",
+            ),
+            (
+                vocab.clone(),
+                json!({"id": "relative/1"}),
+                "relative/1 is relative",
+            ),
+            (
+                json!({"p": {"@id": "https://example.com/#p", "@type": "@id"}}),
+                json!({"p": "relative/2"}),
+                "relative/2 is relative",
+            ),
+            // FAIL: the protected terms of the VC context
+            (
+                json!({"id": "https://example.com/#id"}),
+                json!({}),
+                "protected JSON-LD term id",
+            ),
+            (Value::Null, json!({}), "nullification of protected terms"),
+        ] {
+            assert!(strict(local, properties)
+                .unwrap_err()
+                .to_string()
+                .contains(error));
+        }
+        // the protected terms are enforced by the plain expansion too
+        let doc = json!({
+            "@context": [CREDENTIALS_V1_CONTEXT, {"type": "https://example.com/#type"}],
+            "type": ["VerifiableCredential"]
+        });
+        assert!(expand(&doc, &StaticLoader::default())
+            .unwrap_err()
+            .to_string()
+            .contains("protected JSON-LD term type"));
+    }
+
+    #[test]
+    #[cfg(feature = "proof")]
+    fn test_data_integrity_vectors() {
+        use crate::proof::{create_proof, hash_data, verify_signature};
+        use crate::{Cryptosuite, DidKeyResolver, MemorySigner, PrivateKey};
+
+        // the secretKeyMultibase of a Multikey, a two bytes header and the key
+        let secret =
+            |multibase: &str| bs58::decode(&multibase[1..]).into_vec().unwrap()[2..].to_vec();
+        let hex = |s: &str| {
+            (0..s.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+                .collect::<Vec<u8>>()
+        };
+
+        // the key pairs of the examples of https://www.w3.org/TR/vc-di-eddsa/
+        // and https://www.w3.org/TR/vc-di-ecdsa/
+        let ed25519 = PrivateKey::Ed25519(
+            secret("z3u2en7t5LR2WtQH5PfFqMqwVHBeXouLzo6haApm8XHqvjxq")
+                .try_into()
+                .unwrap(),
+        );
+        let did = DidKeyResolver::did(&ed25519.public_key().unwrap());
+        assert_eq!(
+            did,
+            "did:key:z6MkrJVnaZkeFzdQyMZu1cgjg7k1pZZ6pvBQ7XJPt4swbTQ2"
+        );
+        let p256 = PrivateKey::P256(
+            secret("z42twTcNeSYcnqg1FLuSFs2bsGH3ZqbRHFmvS9XMsYhjxvHN")
+                .try_into()
+                .unwrap(),
+        );
+        assert_eq!(
+            DidKeyResolver::did(&p256.public_key().unwrap()),
+            "did:key:zDnaepBuvsQ8cpsWrVKw8fbpGpvPeNSjVPTWoq6cRqaYzBKVP"
+        );
+        let p384 = PrivateKey::P384(
+            secret("z2fanyY7zgwNpZGxX5fXXibvScNaUWNprHU9dKx7qpVj7mws9J8LLt4mDB5TyH2GLHWkUc")
+                .try_into()
+                .unwrap(),
+        );
+        assert_eq!(
+            DidKeyResolver::did(&p384.public_key().unwrap()),
+            "did:key:z82LkuBieyGShVBhvtE2zoiD6Kma4tJGFtkAhxR5pfkp5QPw4LutoYWhvQCnGjdVn14kujQ"
+        );
+
+        // the eddsa-jcs-2022 example of vc-di-eddsa, the hashes of the canonical proof
+        // options and credential and the proofValue, Ed25519 signatures are deterministic
+        let credential = json!({
+            "@context": [
+                "https://www.w3.org/ns/credentials/v2",
+                "https://www.w3.org/ns/credentials/examples/v2"
+            ],
+            "id": "urn:uuid:58172aac-d8ba-11ed-83dd-0b3aef56cc33",
+            "type": ["VerifiableCredential", "AlumniCredential"],
+            "name": "Alumni Credential",
+            "description": "A minimum viable example of an Alumni Credential.",
+            "issuer": "https://vc.example/issuers/5678",
+            "validFrom": "2023-01-01T00:00:00Z",
+            "credentialSubject": {
+                "id": "did:example:abcdefgh",
+                "alumniOf": "The School of Examples"
+            }
+        });
+        let vm = format!("{}#{}", did, &did[8..]);
+        let created = datetime::parse_rfc3339("2023-02-24T23:36:38Z").unwrap();
+        let proof = create_proof(
+            &credential,
+            &MemorySigner::new(ed25519, &vm),
+            Cryptosuite::EddsaJcs2022,
+            created,
+        )
+        .unwrap();
+        let proof_value = "z2HnFSSPPBzR36zdDgK8PbEHeXbR56YF24jwMpt3R1eHXQzJDMWS93FCzpvJpwTWd3GAVFuUfjoJdcnTMuVor51aX";
+        assert_eq!(proof["proofValue"], proof_value);
+        let mut options = proof.clone();
+        options.as_object_mut().unwrap().remove("proofValue");
+        let data = hash_data(
+            Cryptosuite::EddsaJcs2022,
+            crate::Algorithm::EdDSA,
+            &credential,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(
+            data,
+            [
+                hex("66ab154f5c2890a140cb8388a22a160454f80575f6eae09e5a097cabe539a1db"),
+                hex("59b7cb6251b8991add1ce0bc83107e3db9dbbab5bd2c28f687db1a03abc92f19"),
+            ]
+            .concat()
+        );
+        let signature = bs58::decode(&proof_value[1..]).into_vec().unwrap();
+        let key = crate::PublicKey::from_multibase(&did[8..]).unwrap();
+        verify_signature(&key, &data, &signature).unwrap();
+        // FAIL: the proofValue doesn't verify another credential
+        let mut other = credential.clone();
+        other["credentialSubject"]["alumniOf"] = json!("Another School");
+        let data = hash_data(
+            Cryptosuite::EddsaJcs2022,
+            crate::Algorithm::EdDSA,
+            &other,
+            &options,
+        )
+        .unwrap();
+        assert!(verify_signature(&key, &data, &signature).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "ffi")]
+    fn test_ffi() {
+        use crate::ffi::*;
+        use std::ffi::{CStr, CString};
+        use std::ptr;
+
+        unsafe {
+            let id = CString::new("test-1").unwrap();
+            let mut rl = ptr::null_mut();
+            assert_eq!(rl2020_list_new(id.as_ptr(), 16, &mut rl), Rl2020Result::Ok);
+            assert_eq!(rl2020_list_revoke(rl, 9), Rl2020Result::Ok);
+
+            let mut revoked = false;
+            assert_eq!(
+                rl2020_list_is_revoked(rl, 9, &mut revoked),
+                Rl2020Result::Ok
+            );
+            assert!(revoked);
+
+            // FAIL: out of range
+            assert_eq!(rl2020_list_revoke(rl, 1 << 20), Rl2020Result::Failed);
+            let msg = CStr::from_ptr(rl2020_last_error()).to_str().unwrap();
+            assert!(msg.contains("out of range"));
+            // FAIL: null pointers
+            assert_eq!(
+                rl2020_list_reset(ptr::null_mut(), 9),
+                Rl2020Result::NullPointer
+            );
+            assert_eq!(
+                rl2020_list_is_revoked(rl, 9, ptr::null_mut()),
+                Rl2020Result::NullPointer
+            );
+
+            // round trip through the JSON representation
+            let mut json = ptr::null_mut();
+            assert_eq!(rl2020_list_serialize(rl, &mut json), Rl2020Result::Ok);
+            let mut parsed = ptr::null_mut();
+            assert_eq!(rl2020_list_parse(json, &mut parsed), Rl2020Result::Ok);
+            assert_eq!(rl2020_list_reset(parsed, 9), Rl2020Result::Ok);
+            assert_eq!(
+                rl2020_list_is_revoked(parsed, 9, &mut revoked),
+                Rl2020Result::Ok
+            );
+            assert!(!revoked);
+            // FAIL: invalid json
+            let invalid = CString::new("{}").unwrap();
+            assert_eq!(
+                rl2020_list_parse(invalid.as_ptr(), &mut parsed),
+                Rl2020Result::Failed
+            );
+
+            rl2020_string_free(json);
+            rl2020_list_free(parsed);
+            rl2020_list_free(rl);
+        }
+
+        // the header in include/ is the one generated by build.rs
+        assert_eq!(
+            include_str!("../include/rl2020.h"),
+            include_str!(concat!(env!("OUT_DIR"), "/rl2020.h")),
+            "include/rl2020.h is out of date, copy it from the OUT_DIR of the build"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "python")]
+    fn test_python() {
+        use pyo3::prelude::*;
+        use pyo3::types::PyModule;
+
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = PyModule::new(py, "rl2020").unwrap();
+            crate::python::rl2020(&module).unwrap();
+            py.import("sys")
+                .unwrap()
+                .getattr("modules")
+                .unwrap()
+                .set_item("rl2020", module)
+                .unwrap();
+
+            let code = std::ffi::CString::new(
+                r#"
+import rl2020
+rl = rl2020.RevocationList2020("https://example.com/status/1", 16)
+rl.revoke(3)
+rl.revoke(42)
+assert list(rl) == [3, 42]
+assert rl.get(3) and not rl.get(4)
+
+credential = {"credentialStatus": {
+    "type": "RevocationList2020Status",
+    "revocationListIndex": "42",
+    "revocationListCredential": "https://example.com/status/1",
+}}
+assert rl.is_revoked(credential)
+rl.reset_credential(credential)
+assert not rl.is_revoked(credential)
+
+parsed = rl2020.RevocationList2020.from_json(rl.to_json())
+assert parsed.revoked_indexes() == [3]
+assert not rl2020.is_revoked(rl.to_json(), credential)
+
+# FAIL: out of range
+try:
+    rl.revoke(1 << 20)
+    assert False
+except ValueError:
+    pass
+"#,
+            )
+            .unwrap();
+            py.run(&code, None, None).unwrap();
+        });
+    }
 }
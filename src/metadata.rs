@@ -0,0 +1,144 @@
+use crate::{unix_now, CredentialError, RevocationList2020, RevocationStatus};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+/// RevocationReason is the reason a credential has been revoked,
+/// modeled after the CRL reason codes of RFC 5280
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RevocationReason {
+    Unspecified,
+    KeyCompromise,
+    CaCompromise,
+    AffiliationChanged,
+    Superseded,
+    CessationOfOperation,
+    CertificateHold,
+    PrivilegeWithdrawn,
+    AaCompromise,
+}
+
+impl Display for RevocationReason {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let s = match self {
+            RevocationReason::Unspecified => "unspecified",
+            RevocationReason::KeyCompromise => "keyCompromise",
+            RevocationReason::CaCompromise => "caCompromise",
+            RevocationReason::AffiliationChanged => "affiliationChanged",
+            RevocationReason::Superseded => "superseded",
+            RevocationReason::CessationOfOperation => "cessationOfOperation",
+            RevocationReason::CertificateHold => "certificateHold",
+            RevocationReason::PrivilegeWithdrawn => "privilegeWithdrawn",
+            RevocationReason::AaCompromise => "aaCompromise",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// RevocationMetadata holds the private details of a revocation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RevocationMetadata {
+    #[serde(rename = "reason")]
+    pub reason: RevocationReason,
+    /// unix timestamp (in seconds) of the revocation
+    #[serde(rename = "timestamp")]
+    pub timestamp: u64,
+}
+
+/// MetadataStore is a side table of revocation metadata keyed by index.
+/// It is never part of the published revocation list
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetadataStore {
+    #[serde(rename = "entries")]
+    entries: BTreeMap<u64, RevocationMetadata>,
+}
+
+impl MetadataStore {
+    pub fn get(&self, index: u64) -> Option<&RevocationMetadata> {
+        self.entries.get(&index)
+    }
+
+    pub fn insert(&mut self, index: u64, metadata: RevocationMetadata) {
+        self.entries.insert(index, metadata);
+    }
+
+    pub fn remove(&mut self, index: u64) -> Option<RevocationMetadata> {
+        self.entries.remove(&index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// iterates over the entries ordered by index
+    pub fn iter(&self) -> impl Iterator<Item = (u64, &RevocationMetadata)> {
+        self.entries.iter().map(|(i, m)| (*i, m))
+    }
+
+    /// returns the indexes revoked for the given reason at or after
+    /// the given unix timestamp, ordered by index
+    pub fn revoked_for(&self, reason: RevocationReason, since: u64) -> Vec<u64> {
+        self.iter()
+            .filter(|(_, m)| m.reason == reason && m.timestamp >= since)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    pub fn to_json(&self) -> Result<String, CredentialError> {
+        serde_json::to_string(self).map_err(|e| CredentialError::new(&e.to_string()))
+    }
+
+    pub fn from_json(data: &str) -> Result<Self, CredentialError> {
+        serde_json::from_str(data).map_err(|e| CredentialError::new(&e.to_string()))
+    }
+}
+
+impl RevocationList2020 {
+    /// starts keeping the revocation metadata of the list,
+    /// it has no effect if the metadata store is already enabled
+    pub fn enable_metadata(&mut self) {
+        if self.metadata.is_none() {
+            self.metadata = Some(MetadataStore::default());
+        }
+    }
+
+    /// attaches an existing metadata store to the list
+    pub fn set_metadata(&mut self, metadata: MetadataStore) {
+        self.metadata = Some(metadata);
+    }
+
+    /// returns the revocation metadata of the list, if enabled
+    pub fn metadata(&self) -> Option<&MetadataStore> {
+        self.metadata.as_ref()
+    }
+
+    /// revokes the index recording the reason in the metadata store.
+    /// It fails if the metadata store is not enabled
+    pub fn revoke_with_reason(
+        &mut self,
+        index: u64,
+        reason: RevocationReason,
+    ) -> Result<(), CredentialError> {
+        if self.metadata.is_none() {
+            return Err(CredentialError::new(
+                "the revocation metadata store is not enabled",
+            ));
+        }
+        self.update_as(RevocationStatus::Revoke, index, "", &reason.to_string())?;
+        if let Some(metadata) = self.metadata.as_mut() {
+            metadata.insert(
+                index,
+                RevocationMetadata {
+                    reason,
+                    timestamp: unix_now(),
+                },
+            );
+        }
+        Ok(())
+    }
+}
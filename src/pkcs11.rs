@@ -0,0 +1,390 @@
+use crate::{CredentialError, PublicKey};
+use crate::{Pkcs11Mechanism, Pkcs11Session};
+use libloading::Library;
+use std::ffi::c_void;
+use std::os::raw::c_ulong;
+use std::path::Path;
+use std::ptr;
+
+// A minimal binding of the PKCS#11 (cryptoki) C API, loaded at runtime from the
+// module of the token, e.g. libsofthsm2.so, with the few functions needed to
+// log in, find the keys by label and sign. The functions are taken from the list
+// returned by C_GetFunctionList, the structs use the natural alignment of the
+// unix platforms, see https://docs.oasis-open.org/pkcs11/pkcs11-base/v2.40/
+
+type CkUlong = c_ulong;
+type CkRv = CkUlong;
+type CkSessionHandle = CkUlong;
+type CkObjectHandle = CkUlong;
+
+const CKR_OK: CkRv = 0;
+const CKR_USER_ALREADY_LOGGED_IN: CkRv = 0x100;
+const CKR_CRYPTOKI_ALREADY_INITIALIZED: CkRv = 0x191;
+const CKF_SERIAL_SESSION: CkUlong = 0x4;
+const CKU_USER: CkUlong = 1;
+const CKA_CLASS: CkUlong = 0x0;
+const CKA_LABEL: CkUlong = 0x3;
+const CKA_KEY_TYPE: CkUlong = 0x100;
+const CKA_EC_POINT: CkUlong = 0x181;
+const CKO_PUBLIC_KEY: CkUlong = 0x2;
+const CKO_PRIVATE_KEY: CkUlong = 0x3;
+const CKK_EC: CkUlong = 0x3;
+const CKK_EC_EDWARDS: CkUlong = 0x40;
+const CKM_ECDSA: CkUlong = 0x1041;
+const CKM_EDDSA: CkUlong = 0x1057;
+
+#[repr(C)]
+struct CkAttribute {
+    typ: CkUlong,
+    value: *mut c_void,
+    len: CkUlong,
+}
+
+#[repr(C)]
+struct CkMechanism {
+    mechanism: CkUlong,
+    parameter: *mut c_void,
+    len: CkUlong,
+}
+
+#[repr(C)]
+struct CkTokenInfo {
+    label: [u8; 32],
+    _manufacturer_id: [u8; 32],
+    _model: [u8; 16],
+    _serial_number: [u8; 16],
+    _flags: CkUlong,
+    _counters: [CkUlong; 10],
+    _versions: [u8; 4],
+    _utc_time: [u8; 16],
+}
+
+type Unused = Option<unsafe extern "C" fn()>;
+
+// CkFunctionList is the head of CK_FUNCTION_LIST up to C_Sign,
+// the functions that are not used are only there for the offsets
+#[repr(C)]
+struct CkFunctionList {
+    _version: [u8; 2],
+    initialize: unsafe extern "C" fn(*mut c_void) -> CkRv,
+    finalize: unsafe extern "C" fn(*mut c_void) -> CkRv,
+    _info: [Unused; 2],
+    get_slot_list: unsafe extern "C" fn(u8, *mut CkUlong, *mut CkUlong) -> CkRv,
+    _slot_info: Unused,
+    get_token_info: unsafe extern "C" fn(CkUlong, *mut CkTokenInfo) -> CkRv,
+    _mechanisms_and_pins: [Unused; 5],
+    open_session:
+        unsafe extern "C" fn(CkUlong, CkUlong, *mut c_void, Unused, *mut CkSessionHandle) -> CkRv,
+    close_session: unsafe extern "C" fn(CkSessionHandle) -> CkRv,
+    _sessions_and_state: [Unused; 4],
+    login: unsafe extern "C" fn(CkSessionHandle, CkUlong, *const u8, CkUlong) -> CkRv,
+    logout: unsafe extern "C" fn(CkSessionHandle) -> CkRv,
+    _objects: [Unused; 4],
+    get_attribute_value:
+        unsafe extern "C" fn(CkSessionHandle, CkObjectHandle, *mut CkAttribute, CkUlong) -> CkRv,
+    _set_attribute_value: Unused,
+    find_objects_init: unsafe extern "C" fn(CkSessionHandle, *mut CkAttribute, CkUlong) -> CkRv,
+    find_objects:
+        unsafe extern "C" fn(CkSessionHandle, *mut CkObjectHandle, CkUlong, *mut CkUlong) -> CkRv,
+    find_objects_final: unsafe extern "C" fn(CkSessionHandle) -> CkRv,
+    _encrypt_decrypt_digest: [Unused; 13],
+    sign_init: unsafe extern "C" fn(CkSessionHandle, *mut CkMechanism, CkObjectHandle) -> CkRv,
+    sign: unsafe extern "C" fn(CkSessionHandle, *const u8, CkUlong, *mut u8, *mut CkUlong) -> CkRv,
+}
+
+// check turns the return value of a function into an error with its name
+fn check(function: &str, rv: CkRv) -> Result<(), CredentialError> {
+    match rv {
+        CKR_OK => Ok(()),
+        rv => Err(CredentialError::new(&format!(
+            "{} failed with CKR 0x{:x}",
+            function, rv
+        ))),
+    }
+}
+
+/// Pkcs11Module is a PKCS#11 module loaded from its shared library,
+/// it is finalized when dropped if it was initialized by it
+pub struct Pkcs11Module {
+    functions: *const CkFunctionList,
+    finalize: bool,
+    // the functions point into the library, that must outlive them
+    _library: Library,
+}
+
+impl Pkcs11Module {
+    /// loads and initializes the PKCS#11 module at path, e.g. libsofthsm2.so
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CredentialError> {
+        let path = path.as_ref();
+        let invalid = |e: &dyn std::fmt::Display| {
+            CredentialError::new(&format!(
+                "cannot load the PKCS#11 module {}: {}",
+                path.display(),
+                e
+            ))
+        };
+        // loading a PKCS#11 module runs its initializers, the caller trusts the module
+        let library = unsafe { Library::new(path) }.map_err(|e| invalid(&e))?;
+        let mut functions: *const CkFunctionList = ptr::null();
+        // C_GetFunctionList has this signature in every PKCS#11 version
+        unsafe {
+            let get_function_list = library
+                .get::<unsafe extern "C" fn(*mut *const CkFunctionList) -> CkRv>(
+                    b"C_GetFunctionList\0",
+                )
+                .map_err(|e| invalid(&e))?;
+            check("C_GetFunctionList", get_function_list(&mut functions))?;
+        }
+        if functions.is_null() {
+            return Err(invalid(&"C_GetFunctionList returned no functions"));
+        }
+        // the list is valid while the library is loaded
+        let rv = unsafe { ((*functions).initialize)(ptr::null_mut()) };
+        if rv != CKR_CRYPTOKI_ALREADY_INITIALIZED {
+            check("C_Initialize", rv)?;
+        }
+        Ok(Pkcs11Module {
+            functions,
+            finalize: rv == CKR_OK,
+            _library: library,
+        })
+    }
+
+    fn functions(&self) -> &CkFunctionList {
+        // checked not null in load, valid while the library is loaded
+        unsafe { &*self.functions }
+    }
+
+    /// returns the slot of the token with the label
+    pub fn slot(&self, token_label: &str) -> Result<Pkcs11Slot, CredentialError> {
+        let f = self.functions();
+        let mut count: CkUlong = 0;
+        check("C_GetSlotList", unsafe {
+            (f.get_slot_list)(1, ptr::null_mut(), &mut count)
+        })?;
+        let mut slots = vec![0 as CkUlong; count as usize];
+        check("C_GetSlotList", unsafe {
+            (f.get_slot_list)(1, slots.as_mut_ptr(), &mut count)
+        })?;
+        slots.truncate(count as usize);
+        for slot in slots {
+            let mut info: CkTokenInfo = unsafe { std::mem::zeroed() };
+            check("C_GetTokenInfo", unsafe {
+                (f.get_token_info)(slot, &mut info)
+            })?;
+            // the labels are padded with blanks
+            if String::from_utf8_lossy(&info.label).trim_end() == token_label {
+                return Ok(Pkcs11Slot(slot));
+            }
+        }
+        Err(CredentialError::new(&format!(
+            "no PKCS#11 token with label {}",
+            token_label
+        )))
+    }
+
+    /// opens a session with the token in the slot and logs in the user with the pin
+    pub fn login(
+        &self,
+        slot: Pkcs11Slot,
+        pin: &str,
+    ) -> Result<Pkcs11TokenSession<'_>, CredentialError> {
+        let f = self.functions();
+        let mut handle: CkSessionHandle = 0;
+        check("C_OpenSession", unsafe {
+            (f.open_session)(
+                slot.0,
+                CKF_SERIAL_SESSION,
+                ptr::null_mut(),
+                None,
+                &mut handle,
+            )
+        })?;
+        // the session is closed on drop even if the login fails
+        let session = Pkcs11TokenSession {
+            module: self,
+            handle,
+        };
+        let rv = unsafe { (f.login)(handle, CKU_USER, pin.as_ptr(), pin.len() as CkUlong) };
+        if rv != CKR_USER_ALREADY_LOGGED_IN {
+            check("C_Login", rv)?;
+        }
+        Ok(session)
+    }
+}
+
+impl Drop for Pkcs11Module {
+    fn drop(&mut self) {
+        if self.finalize {
+            // the sessions borrow the module, none is left open
+            unsafe { (self.functions().finalize)(ptr::null_mut()) };
+        }
+    }
+}
+
+/// Pkcs11Slot is the id of a slot with a token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pkcs11Slot(CkUlong);
+
+/// Pkcs11Key is the handle of a key object of a token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pkcs11Key(CkObjectHandle);
+
+/// Pkcs11TokenSession is a logged in session of a token of a Pkcs11Module,
+/// it is logged out and closed when dropped
+pub struct Pkcs11TokenSession<'a> {
+    module: &'a Pkcs11Module,
+    handle: CkSessionHandle,
+}
+
+impl Pkcs11TokenSession<'_> {
+    // find returns the only object of the class with the label
+    fn find(&self, class: CkUlong, label: &str) -> Result<CkObjectHandle, CredentialError> {
+        let f = self.module.functions();
+        let mut class = class;
+        let mut template = [
+            CkAttribute {
+                typ: CKA_CLASS,
+                value: &mut class as *mut CkUlong as *mut c_void,
+                len: std::mem::size_of::<CkUlong>() as CkUlong,
+            },
+            CkAttribute {
+                typ: CKA_LABEL,
+                value: label.as_ptr() as *mut c_void,
+                len: label.len() as CkUlong,
+            },
+        ];
+        let mut objects = [0 as CkObjectHandle; 2];
+        let mut count: CkUlong = 0;
+        // the template and the objects outlive the search, that is always finalized
+        unsafe {
+            check(
+                "C_FindObjectsInit",
+                (f.find_objects_init)(self.handle, template.as_mut_ptr(), 2),
+            )?;
+            let rv = (f.find_objects)(self.handle, objects.as_mut_ptr(), 2, &mut count);
+            check("C_FindObjectsFinal", (f.find_objects_final)(self.handle))?;
+            check("C_FindObjects", rv)?;
+        }
+        match count {
+            1 => Ok(objects[0]),
+            0 => Err(CredentialError::new(&format!(
+                "no PKCS#11 key with label {}",
+                label
+            ))),
+            _ => Err(CredentialError::new(&format!(
+                "more than one PKCS#11 key with label {}",
+                label
+            ))),
+        }
+    }
+
+    // attribute returns the value of an attribute of an object
+    fn attribute(&self, object: CkObjectHandle, typ: CkUlong) -> Result<Vec<u8>, CredentialError> {
+        let f = self.module.functions();
+        let mut attribute = CkAttribute {
+            typ,
+            value: ptr::null_mut(),
+            len: 0,
+        };
+        // the first call only reads the length of the value
+        check("C_GetAttributeValue", unsafe {
+            (f.get_attribute_value)(self.handle, object, &mut attribute, 1)
+        })?;
+        let mut value = vec![0u8; attribute.len as usize];
+        attribute.value = value.as_mut_ptr() as *mut c_void;
+        check("C_GetAttributeValue", unsafe {
+            (f.get_attribute_value)(self.handle, object, &mut attribute, 1)
+        })?;
+        value.truncate(attribute.len as usize);
+        Ok(value)
+    }
+
+    /// returns the private key object with the label
+    pub fn private_key(&self, label: &str) -> Result<Pkcs11Key, CredentialError> {
+        self.find(CKO_PRIVATE_KEY, label).map(Pkcs11Key)
+    }
+
+    /// returns the public key of the public key object with the label,
+    /// an Ed25519, P-256 or P-384 key
+    pub fn public_key(&self, label: &str) -> Result<PublicKey, CredentialError> {
+        let object = self.find(CKO_PUBLIC_KEY, label)?;
+        let key_type = self.attribute(object, CKA_KEY_TYPE)?;
+        let key_type =
+            CkUlong::from_ne_bytes(key_type.try_into().map_err(|_| {
+                CredentialError::new("invalid CKA_KEY_TYPE of the PKCS#11 public key")
+            })?);
+        let mut point = self.attribute(object, CKA_EC_POINT)?;
+        // the point is usually DER encoded as an OCTET STRING
+        if point.len() > 2 && point[0] == 0x04 && point[1] as usize == point.len() - 2 {
+            point.drain(..2);
+        }
+        let unsupported = || CredentialError::new("unsupported PKCS#11 public key");
+        match (key_type, point.len()) {
+            (CKK_EC_EDWARDS, 32) => Ok(PublicKey::Ed25519(
+                point.try_into().map_err(|_| unsupported())?,
+            )),
+            (CKK_EC, 65) => p256::EncodedPoint::from_bytes(&point)
+                .map(|p| PublicKey::P256(p.compress().as_bytes().to_vec()))
+                .map_err(|_| unsupported()),
+            (CKK_EC, 97) => p384::EncodedPoint::from_bytes(&point)
+                .map(|p| PublicKey::P384(p.compress().as_bytes().to_vec()))
+                .map_err(|_| unsupported()),
+            _ => Err(unsupported()),
+        }
+    }
+}
+
+impl Pkcs11Session for Pkcs11TokenSession<'_> {
+    type Key = Pkcs11Key;
+
+    fn sign(
+        &self,
+        mechanism: Pkcs11Mechanism,
+        key: &Pkcs11Key,
+        data: &[u8],
+    ) -> Result<Vec<u8>, CredentialError> {
+        let f = self.module.functions();
+        let mut mechanism = CkMechanism {
+            mechanism: match mechanism {
+                Pkcs11Mechanism::Eddsa => CKM_EDDSA,
+                Pkcs11Mechanism::Ecdsa => CKM_ECDSA,
+            },
+            parameter: ptr::null_mut(),
+            len: 0,
+        };
+        // the signatures of the supported keys are at most 96 bytes
+        let mut signature = vec![0u8; 128];
+        let mut len = signature.len() as CkUlong;
+        // C_Sign writes at most len bytes and ends the operation
+        unsafe {
+            check(
+                "C_SignInit",
+                (f.sign_init)(self.handle, &mut mechanism, key.0),
+            )?;
+            check(
+                "C_Sign",
+                (f.sign)(
+                    self.handle,
+                    data.as_ptr(),
+                    data.len() as CkUlong,
+                    signature.as_mut_ptr(),
+                    &mut len,
+                ),
+            )?;
+        }
+        signature.truncate(len as usize);
+        Ok(signature)
+    }
+}
+
+impl Drop for Pkcs11TokenSession<'_> {
+    fn drop(&mut self) {
+        let f = self.module.functions();
+        // the session is open, the errors cannot be reported
+        unsafe {
+            (f.logout)(self.handle);
+            (f.close_session)(self.handle);
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use crate::datetime::format_rfc3339;
+use crate::did::{resolve_verification_method, DidResolver, PublicKey};
+use crate::jsonld::expand_strict;
+use crate::{rdfc, Algorithm, CredentialError, Signer, StaticLoader};
+use p256::ecdsa::signature::Verifier as _;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256, Sha384};
+
+// Data Integrity proofs of the credentials, see https://www.w3.org/TR/vc-data-integrity/.
+// The eddsa-jcs-2022 and ecdsa-jcs-2019 cryptosuites canonicalize the credential
+// and the proof options with JCS, ecdsa-rdfc-2019 with the RDF canonicalization
+// of the expanded JSON-LD, see https://www.w3.org/TR/vc-di-eddsa/ and
+// https://www.w3.org/TR/vc-di-ecdsa/
+
+pub const DATA_INTEGRITY_PROOF: &str = "DataIntegrityProof";
+pub const EDDSA_JCS_2022: &str = "eddsa-jcs-2022";
+pub const ECDSA_JCS_2019: &str = "ecdsa-jcs-2019";
+pub const ECDSA_RDFC_2019: &str = "ecdsa-rdfc-2019";
+const ASSERTION_METHOD: &str = "assertionMethod";
+
+/// Cryptosuite is a Data Integrity cryptosuite of the proofs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cryptosuite {
+    /// Ed25519 keys, JCS canonicalization
+    EddsaJcs2022,
+    /// P-256 and P-384 keys, JCS canonicalization
+    EcdsaJcs2019,
+    /// P-256 and P-384 keys, RDF canonicalization
+    EcdsaRdfc2019,
+}
+
+impl Cryptosuite {
+    /// returns the name of the cryptosuite, e.g. eddsa-jcs-2022
+    pub fn name(&self) -> &'static str {
+        match self {
+            Cryptosuite::EddsaJcs2022 => EDDSA_JCS_2022,
+            Cryptosuite::EcdsaJcs2019 => ECDSA_JCS_2019,
+            Cryptosuite::EcdsaRdfc2019 => ECDSA_RDFC_2019,
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [
+            Cryptosuite::EddsaJcs2022,
+            Cryptosuite::EcdsaJcs2019,
+            Cryptosuite::EcdsaRdfc2019,
+        ]
+        .into_iter()
+        .find(|c| c.name() == name)
+    }
+
+    // check_algorithm checks that the cryptosuite signs with the algorithm
+    fn check_algorithm(&self, algorithm: Algorithm) -> Result<(), CredentialError> {
+        match (self, algorithm) {
+            (Cryptosuite::EddsaJcs2022, Algorithm::EdDSA)
+            | (
+                Cryptosuite::EcdsaJcs2019 | Cryptosuite::EcdsaRdfc2019,
+                Algorithm::ES256 | Algorithm::ES384,
+            ) => Ok(()),
+            _ => Err(CredentialError::new(&format!(
+                "the key of the verification method cannot verify {} proofs",
+                self.name()
+            ))),
+        }
+    }
+
+    // canonicalize returns the canonical form of a document for the cryptosuite
+    fn canonicalize(&self, document: &Value) -> Result<String, CredentialError> {
+        match self {
+            Cryptosuite::EcdsaRdfc2019 => {
+                rdfc::canonicalize(&expand_strict(document, &StaticLoader::default())?)
+            }
+            _ => serde_jcs::to_string(document).map_err(|e| CredentialError::new(&e.to_string())),
+        }
+    }
+}
+
+// hash hashes the data with SHA-384 for ES384, SHA-256 otherwise
+fn hash(algorithm: Algorithm, data: &str) -> Vec<u8> {
+    match algorithm {
+        Algorithm::ES384 => Sha384::digest(data.as_bytes()).to_vec(),
+        _ => Sha256::digest(data.as_bytes()).to_vec(),
+    }
+}
+
+/// returns the data signed by the proof, the hash of the proof
+/// options followed by the hash of the document without the proof
+pub(crate) fn hash_data(
+    cryptosuite: Cryptosuite,
+    algorithm: Algorithm,
+    document: &Value,
+    options: &Value,
+) -> Result<Vec<u8>, CredentialError> {
+    let mut document = document.clone();
+    if let Value::Object(m) = &mut document {
+        m.remove("proof");
+    }
+    Ok([
+        hash(algorithm, &cryptosuite.canonicalize(options)?),
+        hash(algorithm, &cryptosuite.canonicalize(&document)?),
+    ]
+    .concat())
+}
+
+/// creates the proof of the document with the cryptosuite, signed by the
+/// signer at the unix timestamp created
+pub(crate) fn create_proof(
+    document: &Value,
+    signer: &impl Signer,
+    cryptosuite: Cryptosuite,
+    created: u64,
+) -> Result<Value, CredentialError> {
+    let algorithm = signer.algorithm();
+    cryptosuite.check_algorithm(algorithm)?;
+    let mut options = Map::new();
+    if let Some(context) = document.get("@context") {
+        options.insert("@context".into(), context.clone());
+    }
+    options.insert("type".into(), DATA_INTEGRITY_PROOF.into());
+    options.insert("cryptosuite".into(), cryptosuite.name().into());
+    options.insert("created".into(), format_rfc3339(created).into());
+    options.insert("verificationMethod".into(), signer.key_id().into());
+    options.insert("proofPurpose".into(), ASSERTION_METHOD.into());
+    let mut proof = Value::Object(options);
+    let signature = signer.sign(&hash_data(cryptosuite, algorithm, document, &proof)?)?;
+    proof["proofValue"] = format!("z{}", bs58::encode(signature).into_string()).into();
+    Ok(proof)
+}
+
+/// verifies the signature of the data with the public key,
+/// the ECDSA signatures are the concatenation of r and s
+pub(crate) fn verify_signature(
+    key: &PublicKey,
+    data: &[u8],
+    signature: &[u8],
+) -> Result<(), CredentialError> {
+    let invalid = |e: &dyn std::fmt::Display| {
+        CredentialError::new(&format!("invalid proof signature: {}", e))
+    };
+    match key {
+        PublicKey::Ed25519(key) => {
+            let key = ed25519_dalek::VerifyingKey::from_bytes(key).map_err(|e| invalid(&e))?;
+            let signature =
+                ed25519_dalek::Signature::from_slice(signature).map_err(|e| invalid(&e))?;
+            key.verify(data, &signature).map_err(|e| invalid(&e))
+        }
+        PublicKey::P256(key) => {
+            let key = p256::ecdsa::VerifyingKey::from_sec1_bytes(key).map_err(|e| invalid(&e))?;
+            let signature =
+                p256::ecdsa::Signature::from_slice(signature).map_err(|e| invalid(&e))?;
+            key.verify(data, &signature).map_err(|e| invalid(&e))
+        }
+        PublicKey::P384(key) => {
+            let key = p384::ecdsa::VerifyingKey::from_sec1_bytes(key).map_err(|e| invalid(&e))?;
+            let signature =
+                p384::ecdsa::Signature::from_slice(signature).map_err(|e| invalid(&e))?;
+            key.verify(data, &signature).map_err(|e| invalid(&e))
+        }
+    }
+}
+
+/// verifies the proof of the document resolving its verification method, that
+/// must be an assertionMethod of the issuer
+pub(crate) fn verify_proof(
+    document: &Value,
+    issuer: &str,
+    resolver: &impl DidResolver,
+) -> Result<(), CredentialError> {
+    let proof = document
+        .get("proof")
+        .and_then(|p| p.as_object())
+        .ok_or_else(|| CredentialError::new("the credential has no proof"))?;
+    let field = |name: &str| {
+        proof.get(name).and_then(|v| v.as_str()).ok_or_else(|| {
+            CredentialError::new(&format!("the proof of the credential has no {}", name))
+        })
+    };
+    let cryptosuite = Cryptosuite::from_name(field("cryptosuite")?)
+        .filter(|_| field("type").is_ok_and(|t| t == DATA_INTEGRITY_PROOF))
+        .ok_or_else(|| {
+            CredentialError::new(&format!(
+                "unsupported proof, only {} {}, {} and {} are supported",
+                DATA_INTEGRITY_PROOF, EDDSA_JCS_2022, ECDSA_JCS_2019, ECDSA_RDFC_2019
+            ))
+        })?;
+    if field("proofPurpose")? != ASSERTION_METHOD {
+        return Err(CredentialError::new(&format!(
+            "the purpose of the proof must be {}",
+            ASSERTION_METHOD
+        )));
+    }
+    if proof
+        .get("@context")
+        .is_some_and(|c| Some(c) != document.get("@context"))
+    {
+        return Err(CredentialError::new(
+            "the @context of the proof doesn't match the credential",
+        ));
+    }
+    let method =
+        resolve_verification_method(resolver, field("verificationMethod")?, ASSERTION_METHOD)?;
+    if method.controller != issuer {
+        return Err(CredentialError::new(&format!(
+            "the proof is signed by {}, not by the issuer {}",
+            method.controller, issuer
+        )));
+    }
+    let algorithm = Algorithm::of(&method.key);
+    cryptosuite.check_algorithm(algorithm)?;
+    let signature = field("proofValue")?
+        .strip_prefix('z')
+        .and_then(|s| bs58::decode(s).into_vec().ok())
+        .ok_or_else(|| CredentialError::new("the proofValue must be base58btc multibase"))?;
+    let mut options = proof.clone();
+    options.remove("proofValue");
+    verify_signature(
+        &method.key,
+        &hash_data(cryptosuite, algorithm, document, &Value::Object(options))?,
+        &signature,
+    )
+}
@@ -0,0 +1,177 @@
+use crate::{
+    CredentialError, RevocationList2020, RevocationList2020Credential, RevocationStatus,
+    SimpleCredential,
+};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyString;
+use std::str::FromStr;
+
+// The python bindings, built as the rl2020 module with maturin (see pyproject.toml).
+// Lists and credentials are accepted both as JSON strings and as plain dicts
+
+impl From<CredentialError> for PyErr {
+    fn from(e: CredentialError) -> Self {
+        PyValueError::new_err(e.to_string())
+    }
+}
+
+// to_json returns the JSON of a value that is either a string or a json serializable object
+fn to_json(value: &Bound<'_, PyAny>) -> PyResult<String> {
+    if let Ok(s) = value.downcast::<PyString>() {
+        return s.extract();
+    }
+    value
+        .py()
+        .import("json")?
+        .call_method1("dumps", (value,))?
+        .extract()
+}
+
+// parse_credential parses the credential, keeping its issuer
+// so that the list credentials can check who issued them
+fn parse_credential(value: &Bound<'_, PyAny>) -> PyResult<SimpleCredential> {
+    serde_json::from_str::<SimpleCredential>(&to_json(value)?)
+        .map_err(|e| PyValueError::new_err(format!("error parsing the input credential: {}", e)))
+}
+
+/// RevocationList2020 is the python class wrapping a revocation list
+#[pyclass(name = "RevocationList2020", module = "rl2020")]
+#[derive(Clone)]
+pub struct PyRevocationList2020 {
+    inner: RevocationList2020,
+}
+
+#[pymethods]
+impl PyRevocationList2020 {
+    /// creates an empty revocation list with the given id and size in kb
+    #[new]
+    fn new(id: &str, size: usize) -> PyResult<Self> {
+        Ok(PyRevocationList2020 {
+            inner: RevocationList2020::new(id, size)?,
+        })
+    }
+
+    /// parses a revocation list from a JSON string or a dict
+    #[staticmethod]
+    fn from_json(value: &Bound<'_, PyAny>) -> PyResult<Self> {
+        Ok(PyRevocationList2020 {
+            inner: RevocationList2020::from_str(&to_json(value)?)?,
+        })
+    }
+
+    /// returns the JSON representation of the list
+    fn to_json(&self) -> String {
+        self.inner.to_string()
+    }
+
+    fn __str__(&self) -> String {
+        self.inner.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "RevocationList2020(id={:?}, size={})",
+            self.inner.id(),
+            self.inner.size()
+        )
+    }
+
+    #[getter]
+    fn id(&self) -> String {
+        self.inner.id().to_owned()
+    }
+
+    #[getter]
+    fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    #[getter]
+    fn size(&self) -> usize {
+        self.inner.size()
+    }
+
+    fn revoke(&mut self, index: u64) -> PyResult<()> {
+        Ok(self.inner.update(RevocationStatus::Revoke, index)?)
+    }
+
+    fn reset(&mut self, index: u64) -> PyResult<()> {
+        Ok(self.inner.update(RevocationStatus::Reset, index)?)
+    }
+
+    /// returns true if the index is revoked
+    fn get(&self, index: u64) -> PyResult<bool> {
+        Ok(self.inner.get(index)? == RevocationStatus::Revoke)
+    }
+
+    /// returns the revoked indexes in ascending order
+    fn revoked_indexes(&self) -> Vec<u64> {
+        self.inner.revoked_indexes().collect()
+    }
+
+    /// iterates over the revoked indexes in ascending order
+    fn __iter__(&self) -> RevokedIndexes {
+        RevokedIndexes {
+            inner: self.revoked_indexes().into_iter(),
+        }
+    }
+
+    /// returns true if the credential (a JSON string or a dict) is revoked
+    fn is_revoked(&self, credential: &Bound<'_, PyAny>) -> PyResult<bool> {
+        let credential = parse_credential(credential)?;
+        Ok(self.inner.is_revoked(&credential)?)
+    }
+
+    /// revokes the credential (a JSON string or a dict)
+    fn revoke_credential(&mut self, credential: &Bound<'_, PyAny>) -> PyResult<()> {
+        let credential = parse_credential(credential)?;
+        Ok(self.inner.revoke(&credential)?)
+    }
+
+    /// resets the credential (a JSON string or a dict)
+    fn reset_credential(&mut self, credential: &Bound<'_, PyAny>) -> PyResult<()> {
+        let credential = parse_credential(credential)?;
+        Ok(self.inner.reset(&credential)?)
+    }
+}
+
+/// RevokedIndexes is the iterator over the revoked indexes of a list
+#[pyclass(module = "rl2020")]
+pub struct RevokedIndexes {
+    inner: std::vec::IntoIter<u64>,
+}
+
+#[pymethods]
+impl RevokedIndexes {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<'_, Self>) -> Option<u64> {
+        slf.inner.next()
+    }
+}
+
+/// checks if the credential is revoked in the revocation list, the list can
+/// be a RevocationList2020 or a RevocationList2020Credential, both the list
+/// and the credential can be JSON strings or dicts
+#[pyfunction]
+fn is_revoked(revocation_list: &Bound<'_, PyAny>, credential: &Bound<'_, PyAny>) -> PyResult<bool> {
+    let value: serde_json::Value = serde_json::from_str(&to_json(revocation_list)?)
+        .map_err(|e| PyValueError::new_err(format!("error parsing the revocation list: {}", e)))?;
+    if value.get("credentialSubject").is_some() {
+        let rc = RevocationList2020Credential::from_str(&value.to_string())?;
+        let credential = parse_credential(credential)?;
+        return Ok(rc.is_revoked(&credential)?);
+    }
+    PyRevocationList2020::from_json(revocation_list)?.is_revoked(credential)
+}
+
+#[pymodule]
+pub(crate) fn rl2020(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyRevocationList2020>()?;
+    m.add_class::<RevokedIndexes>()?;
+    m.add_function(wrap_pyfunction!(is_revoked, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,547 @@
+use crate::CredentialError;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+// RDF Dataset Canonicalization (RDFC-1.0) of the expanded JSON-LD documents,
+// used by the ecdsa-rdfc-2019 cryptosuite, see https://www.w3.org/TR/rdf-canon/.
+// The documents are converted to RDF as in
+// https://www.w3.org/TR/json-ld11-api/#deserialize-json-ld-to-rdf-algorithm
+// except that relative IRIs are an error, since they would not be signed, and
+// so are @included and @reverse, that need the node map of the flattening
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const RDF_FIRST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#first";
+const RDF_REST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#rest";
+const RDF_NIL: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#nil";
+const RDF_JSON: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#JSON";
+const RDF_LANG_STRING: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#langString";
+const XSD_STRING: &str = "http://www.w3.org/2001/XMLSchema#string";
+const XSD_BOOLEAN: &str = "http://www.w3.org/2001/XMLSchema#boolean";
+const XSD_INTEGER: &str = "http://www.w3.org/2001/XMLSchema#integer";
+const XSD_DOUBLE: &str = "http://www.w3.org/2001/XMLSchema#double";
+// the hashes of the N-degree quads computed before giving up on a dataset
+// crafted to make the canonicalization too expensive
+const MAX_N_DEGREE_HASHES: usize = 4096;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Term {
+    Iri(String),
+    Blank(String),
+    Literal {
+        value: String,
+        datatype: String,
+        language: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Quad {
+    subject: Term,
+    predicate: String,
+    object: Term,
+    graph: Option<Term>,
+}
+
+impl Quad {
+    // blank_nodes returns the blank nodes of the quad with their position
+    fn blank_nodes(&self) -> impl Iterator<Item = (char, &str)> {
+        [
+            ('s', Some(&self.subject)),
+            ('o', Some(&self.object)),
+            ('g', self.graph.as_ref()),
+        ]
+        .into_iter()
+        .filter_map(|(position, term)| match term {
+            Some(Term::Blank(label)) => Some((position, label.as_str())),
+            _ => None,
+        })
+    }
+
+    // to_nquads serializes the quad, relabeling its blank nodes
+    fn to_nquads(&self, label: &dyn Fn(&str) -> String) -> String {
+        let term = |t: &Term| match t {
+            Term::Iri(iri) => format!("<{}>", iri),
+            Term::Blank(l) => format!("_:{}", label(l)),
+            Term::Literal {
+                value,
+                datatype,
+                language,
+            } => match language {
+                Some(language) => format!("\"{}\"@{}", escape(value), language),
+                None if datatype == XSD_STRING => format!("\"{}\"", escape(value)),
+                None => format!("\"{}\"^^<{}>", escape(value), datatype),
+            },
+        };
+        match &self.graph {
+            Some(g) => format!(
+                "{} <{}> {} {} .\n",
+                term(&self.subject),
+                self.predicate,
+                term(&self.object),
+                term(g)
+            ),
+            None => format!(
+                "{} <{}> {} .\n",
+                term(&self.subject),
+                self.predicate,
+                term(&self.object)
+            ),
+        }
+    }
+}
+
+// escape escapes a literal as in the canonical form of N-Quads
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\u{8}' => out.push_str("\\b"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\u{c}' => out.push_str("\\f"),
+            '\r' => out.push_str("\\r"),
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{0}'..='\u{1f}' | '\u{7f}' => out.push_str(&format!("\\u{:04X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn sha256_hex(data: &str) -> String {
+    Sha256::digest(data.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn is_absolute(iri: &str) -> bool {
+    match iri.split_once(':') {
+        Some((scheme, _)) => {
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+        }
+        None => false,
+    }
+}
+
+// canonical_double formats a double as in the canonical form of xsd:double, e.g. 1.1E0
+fn canonical_double(f: f64) -> String {
+    let s = format!("{:.15e}", f);
+    let (mantissa, exponent) = s.split_once('e').unwrap_or((&s, "0"));
+    let mantissa = mantissa.trim_end_matches('0');
+    match mantissa.ends_with('.') {
+        true => format!("{}0E{}", mantissa, exponent),
+        false => format!("{}E{}", mantissa, exponent),
+    }
+}
+
+fn literal(value: &Map<String, Value>) -> Result<Term, CredentialError> {
+    let typ = value.get("@type").and_then(|t| t.as_str());
+    let datatype = |default: &str| typ.unwrap_or(default).to_string();
+    let (lexical, datatype) = match (&value["@value"], typ) {
+        (v, Some("@json")) => (
+            serde_jcs::to_string(v).map_err(|e| CredentialError::new(&e.to_string()))?,
+            RDF_JSON.to_string(),
+        ),
+        (Value::Bool(b), _) => (b.to_string(), datatype(XSD_BOOLEAN)),
+        (Value::Number(n), _) => {
+            let f = n.as_f64().unwrap_or_default();
+            match (n.as_i64(), n.as_u64()) {
+                _ if typ == Some(XSD_DOUBLE) => (canonical_double(f), datatype(XSD_DOUBLE)),
+                (Some(i), _) => (i.to_string(), datatype(XSD_INTEGER)),
+                (_, Some(u)) => (u.to_string(), datatype(XSD_INTEGER)),
+                _ if f.fract() == 0.0 && f.abs() < 1e21 => {
+                    (format!("{:.0}", f), datatype(XSD_INTEGER))
+                }
+                _ => (canonical_double(f), datatype(XSD_DOUBLE)),
+            }
+        }
+        (Value::String(s), _) => match value.get("@language").and_then(|l| l.as_str()) {
+            Some(language) => {
+                return Ok(Term::Literal {
+                    value: s.clone(),
+                    datatype: RDF_LANG_STRING.to_string(),
+                    language: Some(language.to_lowercase()),
+                })
+            }
+            None => (s.clone(), datatype(XSD_STRING)),
+        },
+        (v, _) => {
+            return Err(CredentialError::new(&format!(
+                "invalid JSON-LD value {}",
+                v
+            )))
+        }
+    };
+    if !is_absolute(&datatype) {
+        return Err(CredentialError::new(&format!(
+            "the datatype {} is not an absolute IRI",
+            datatype
+        )));
+    }
+    Ok(Term::Literal {
+        value: lexical,
+        datatype,
+        language: None,
+    })
+}
+
+// Deserializer converts the nodes of an expanded document to quads
+#[derive(Default)]
+struct Deserializer {
+    quads: Vec<Quad>,
+    blank_nodes: usize,
+}
+
+impl Deserializer {
+    fn blank_node(&mut self) -> Term {
+        self.blank_nodes += 1;
+        // the generated labels cannot clash with the ones of the document, that are valid labels
+        Term::Blank(format!("{}#generated", self.blank_nodes))
+    }
+
+    fn iri(&self, iri: &str) -> Result<Term, CredentialError> {
+        match iri.strip_prefix("_:") {
+            Some(label) => Ok(Term::Blank(label.to_string())),
+            None if is_absolute(iri) => Ok(Term::Iri(iri.to_string())),
+            None => Err(CredentialError::new(&format!(
+                "the relative IRI {} cannot be canonicalized",
+                iri
+            ))),
+        }
+    }
+
+    fn push(&mut self, subject: &Term, predicate: &str, object: Term, graph: &Option<Term>) {
+        self.quads.push(Quad {
+            subject: subject.clone(),
+            predicate: predicate.to_string(),
+            object,
+            graph: graph.clone(),
+        });
+    }
+
+    // node adds the quads of a node object to the graph, returning its subject
+    fn node(
+        &mut self,
+        node: &Map<String, Value>,
+        graph: &Option<Term>,
+    ) -> Result<Term, CredentialError> {
+        let subject = match node.get("@id").and_then(|id| id.as_str()) {
+            Some(id) => self.iri(id)?,
+            None => self.blank_node(),
+        };
+        let empty = Vec::new();
+        for (property, values) in node {
+            let values = values.as_array().unwrap_or(&empty);
+            match property.as_str() {
+                "@type" => {
+                    for t in values.iter().filter_map(|t| t.as_str()) {
+                        let t = self.iri(t)?;
+                        self.push(&subject, RDF_TYPE, t, graph);
+                    }
+                }
+                // the nodes of a graph object are in the graph named by the object
+                "@graph" => {
+                    let named = Some(subject.clone());
+                    for n in values.iter().filter_map(|n| n.as_object()) {
+                        self.node(n, &named)?;
+                    }
+                }
+                // dropping their nodes would leave them out of the signature
+                "@included" | "@reverse" => {
+                    return Err(CredentialError::new(&format!(
+                        "the keyword {} cannot be canonicalized",
+                        property
+                    )))
+                }
+                // blank node properties are only valid in generalized RDF
+                p if p.starts_with('@') || p.starts_with("_:") => {}
+                p if !is_absolute(p) => {
+                    return Err(CredentialError::new(&format!(
+                        "the property {} is not an absolute IRI",
+                        p
+                    )))
+                }
+                p => {
+                    for v in values {
+                        let object = self.object(v, graph)?;
+                        self.push(&subject, p, object, graph);
+                    }
+                }
+            }
+        }
+        Ok(subject)
+    }
+
+    fn object(&mut self, value: &Value, graph: &Option<Term>) -> Result<Term, CredentialError> {
+        let value = value
+            .as_object()
+            .ok_or_else(|| CredentialError::new("invalid expanded JSON-LD document"))?;
+        if value.contains_key("@value") {
+            return literal(value);
+        }
+        match value.get("@list").and_then(|l| l.as_array()) {
+            Some(items) => self.list(items, graph),
+            None => self.node(value, graph),
+        }
+    }
+
+    fn list(&mut self, items: &[Value], graph: &Option<Term>) -> Result<Term, CredentialError> {
+        let nodes: Vec<Term> = items.iter().map(|_| self.blank_node()).collect();
+        for (i, item) in items.iter().enumerate() {
+            let object = self.object(item, graph)?;
+            self.push(&nodes[i], RDF_FIRST, object, graph);
+            let rest = nodes
+                .get(i + 1)
+                .cloned()
+                .unwrap_or_else(|| Term::Iri(RDF_NIL.to_string()));
+            self.push(&nodes[i], RDF_REST, rest, graph);
+        }
+        Ok(nodes
+            .first()
+            .cloned()
+            .unwrap_or_else(|| Term::Iri(RDF_NIL.to_string())))
+    }
+}
+
+// IdentifierIssuer issues the new labels of the blank nodes, keeping their order
+#[derive(Debug, Clone)]
+struct IdentifierIssuer {
+    prefix: &'static str,
+    issued: Vec<String>,
+    labels: HashMap<String, String>,
+}
+
+impl IdentifierIssuer {
+    fn new(prefix: &'static str) -> Self {
+        IdentifierIssuer {
+            prefix,
+            issued: Vec::new(),
+            labels: HashMap::new(),
+        }
+    }
+
+    fn get(&self, id: &str) -> Option<&String> {
+        self.labels.get(id)
+    }
+
+    fn issue(&mut self, id: &str) -> String {
+        if let Some(label) = self.labels.get(id) {
+            return label.clone();
+        }
+        let label = format!("{}{}", self.prefix, self.issued.len());
+        self.issued.push(id.to_string());
+        self.labels.insert(id.to_string(), label.clone());
+        label
+    }
+}
+
+fn permutations(items: &[String]) -> Vec<Vec<String>> {
+    if items.len() <= 1 {
+        return vec![items.to_vec()];
+    }
+    let mut out = Vec::new();
+    for i in 0..items.len() {
+        let mut rest = items.to_vec();
+        let first = rest.remove(i);
+        for mut p in permutations(&rest) {
+            p.insert(0, first.clone());
+            out.push(p);
+        }
+    }
+    out
+}
+
+struct Canonicalizer<'a> {
+    quads: &'a [Quad],
+    blank_quads: HashMap<&'a str, Vec<&'a Quad>>,
+    canonical: IdentifierIssuer,
+    first_degree: HashMap<String, String>,
+    n_degree_hashes: usize,
+}
+
+impl<'a> Canonicalizer<'a> {
+    fn new(quads: &'a [Quad]) -> Self {
+        let mut blank_quads: HashMap<&str, Vec<&Quad>> = HashMap::new();
+        for quad in quads {
+            for (_, label) in quad.blank_nodes() {
+                blank_quads.entry(label).or_default().push(quad);
+            }
+        }
+        Canonicalizer {
+            quads,
+            blank_quads,
+            canonical: IdentifierIssuer::new("c14n"),
+            first_degree: HashMap::new(),
+            n_degree_hashes: 0,
+        }
+    }
+
+    fn hash_first_degree(&mut self, id: &str) -> String {
+        if let Some(hash) = self.first_degree.get(id) {
+            return hash.clone();
+        }
+        let label = |l: &str| String::from(if l == id { "a" } else { "z" });
+        let mut lines: Vec<String> = self.blank_quads[id]
+            .iter()
+            .map(|q| q.to_nquads(&label))
+            .collect();
+        lines.sort_unstable();
+        let hash = sha256_hex(&lines.concat());
+        self.first_degree.insert(id.to_string(), hash.clone());
+        hash
+    }
+
+    fn hash_related(
+        &mut self,
+        related: &str,
+        quad: &Quad,
+        issuer: &IdentifierIssuer,
+        position: char,
+    ) -> String {
+        let id = match (self.canonical.get(related), issuer.get(related)) {
+            (Some(label), _) | (None, Some(label)) => format!("_:{}", label),
+            (None, None) => self.hash_first_degree(related),
+        };
+        match position {
+            'g' => sha256_hex(&format!("g{}", id)),
+            _ => sha256_hex(&format!("{}<{}>{}", position, quad.predicate, id)),
+        }
+    }
+
+    fn hash_n_degree(
+        &mut self,
+        id: &str,
+        mut issuer: IdentifierIssuer,
+    ) -> Result<(String, IdentifierIssuer), CredentialError> {
+        self.n_degree_hashes += 1;
+        if self.n_degree_hashes > MAX_N_DEGREE_HASHES {
+            return Err(CredentialError::new(
+                "the document is too complex to be canonicalized",
+            ));
+        }
+        let mut hash_to_related: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for quad in self.blank_quads[id].clone() {
+            for (position, related) in quad.blank_nodes().filter(|(_, l)| *l != id) {
+                let hash = self.hash_related(related, quad, &issuer, position);
+                hash_to_related
+                    .entry(hash)
+                    .or_default()
+                    .push(related.to_string());
+            }
+        }
+        let mut data = String::new();
+        for (hash, related) in hash_to_related {
+            data.push_str(&hash);
+            let mut chosen_path = String::new();
+            let mut chosen_issuer = None;
+            // a path is discarded as soon as it cannot be the smallest one
+            let worse = |path: &str, chosen: &str| {
+                !chosen.is_empty() && path.len() >= chosen.len() && path > chosen
+            };
+            'permutations: for permutation in permutations(&related) {
+                let mut issuer_copy = issuer.clone();
+                let mut path = String::new();
+                let mut recursion = Vec::new();
+                for r in &permutation {
+                    match self.canonical.get(r) {
+                        Some(label) => path.push_str(&format!("_:{}", label)),
+                        None => {
+                            if issuer_copy.get(r).is_none() {
+                                recursion.push(r.clone());
+                            }
+                            path.push_str(&format!("_:{}", issuer_copy.issue(r)));
+                        }
+                    }
+                    if worse(&path, &chosen_path) {
+                        continue 'permutations;
+                    }
+                }
+                for r in recursion {
+                    let (hash, result_issuer) = self.hash_n_degree(&r, issuer_copy.clone())?;
+                    path.push_str(&format!("_:{}<{}>", issuer_copy.issue(&r), hash));
+                    issuer_copy = result_issuer;
+                    if worse(&path, &chosen_path) {
+                        continue 'permutations;
+                    }
+                }
+                if chosen_path.is_empty() || path < chosen_path {
+                    chosen_path = path;
+                    chosen_issuer = Some(issuer_copy);
+                }
+            }
+            data.push_str(&chosen_path);
+            if let Some(chosen) = chosen_issuer {
+                issuer = chosen;
+            }
+        }
+        Ok((sha256_hex(&data), issuer))
+    }
+
+    fn canonicalize(mut self) -> Result<String, CredentialError> {
+        let mut ids: Vec<&str> = self.blank_quads.keys().copied().collect();
+        ids.sort_unstable();
+        let mut hash_to_blank: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for id in ids {
+            let hash = self.hash_first_degree(id);
+            hash_to_blank.entry(hash).or_default().push(id.to_string());
+        }
+        // the blank nodes with a unique hash are labeled first
+        let mut shared = Vec::new();
+        for (_, ids) in hash_to_blank {
+            match ids.as_slice() {
+                [id] => {
+                    self.canonical.issue(id);
+                }
+                _ => shared.push(ids),
+            }
+        }
+        for ids in shared {
+            let mut results = Vec::new();
+            for id in ids {
+                if self.canonical.get(&id).is_some() {
+                    continue;
+                }
+                let mut issuer = IdentifierIssuer::new("b");
+                issuer.issue(&id);
+                results.push(self.hash_n_degree(&id, issuer)?);
+            }
+            results.sort_by(|a, b| a.0.cmp(&b.0));
+            for (_, issuer) in results {
+                for id in issuer.issued {
+                    self.canonical.issue(&id);
+                }
+            }
+        }
+        let label = |l: &str| self.canonical.get(l).cloned().unwrap_or_default();
+        let mut lines: Vec<String> = self.quads.iter().map(|q| q.to_nquads(&label)).collect();
+        lines.sort_unstable();
+        lines.dedup();
+        Ok(lines.concat())
+    }
+}
+
+/// returns the canonical N-Quads of an expanded JSON-LD document
+pub(crate) fn canonicalize(expanded: &Value) -> Result<String, CredentialError> {
+    let mut deserializer = Deserializer::default();
+    let empty = Vec::new();
+    for node in expanded.as_array().unwrap_or(&empty) {
+        let node = node
+            .as_object()
+            .ok_or_else(|| CredentialError::new("invalid expanded JSON-LD document"))?;
+        deserializer.node(node, &None)?;
+    }
+    // a dataset is a set, the duplicated quads would change the hashes
+    let mut seen = HashSet::new();
+    let quads: Vec<Quad> = deserializer
+        .quads
+        .into_iter()
+        .filter(|q| seen.insert(q.to_nquads(&|l| l.to_string())))
+        .collect();
+    Canonicalizer::new(&quads).canonicalize()
+}
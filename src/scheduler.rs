@@ -0,0 +1,216 @@
+use crate::{unix_now, CredentialError, RevocationList2020, RevocationStatus};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const SCHEDULER_ACTOR: &str = "scheduler";
+
+/// Clock provides the current unix timestamp (in seconds) to the scheduler
+pub trait Clock {
+    fn now(&self) -> u64;
+}
+
+/// SystemClock reads the time from the system
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        unix_now()
+    }
+}
+
+impl<C: Clock> Clock for &C {
+    fn now(&self) -> u64 {
+        (*self).now()
+    }
+}
+
+/// ScheduledTransition is a status change that takes effect at a given time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledTransition {
+    /// the id assigned by the scheduler, unique among its transitions
+    #[serde(rename = "id")]
+    pub id: u64,
+    #[serde(rename = "index")]
+    pub index: u64,
+    #[serde(rename = "action")]
+    pub action: RevocationStatus,
+    /// unix timestamp (in seconds) when the transition is due
+    #[serde(rename = "at")]
+    pub at: u64,
+    #[serde(rename = "reason")]
+    pub reason: String,
+}
+
+/// Scheduler keeps the status changes to apply to a revocation list in the future
+#[derive(Debug, Clone, Default)]
+pub struct Scheduler<C: Clock = SystemClock> {
+    clock: C,
+    pending: Vec<ScheduledTransition>,
+    /// the suspended indexes and the id of the reset that ends their suspension
+    suspensions: BTreeMap<u64, u64>,
+    next_id: u64,
+}
+
+impl<C: Clock> Scheduler<C> {
+    pub fn new(clock: C) -> Self {
+        Scheduler {
+            clock,
+            pending: Vec::new(),
+            suspensions: BTreeMap::new(),
+            next_id: 0,
+        }
+    }
+
+    /// returns the transitions that are not yet applied, ordered by due time
+    pub fn pending(&self) -> &[ScheduledTransition] {
+        &self.pending
+    }
+
+    /// schedules a status change for the index at the given time, returning its id
+    pub fn schedule(&mut self, action: RevocationStatus, index: u64, at: u64, reason: &str) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        let t = ScheduledTransition {
+            id,
+            index,
+            action,
+            at,
+            reason: String::from(reason),
+        };
+        // keep the pending transitions sorted by due time, preserving
+        // the insertion order of the ones due at the same time
+        let pos = self.pending.partition_point(|p| p.at <= at);
+        self.pending.insert(pos, t);
+        id
+    }
+
+    /// schedules the revocation of the index at the given time, returning its id
+    pub fn schedule_revocation(&mut self, index: u64, at: u64) -> u64 {
+        self.schedule(RevocationStatus::Revoke, index, at, "scheduled revocation")
+    }
+
+    /// returns true if the index is suspended, that is it has been revoked by
+    /// suspend and it will be reset when the suspension expires
+    pub fn is_suspended(&self, index: u64) -> bool {
+        self.suspensions.contains_key(&index)
+    }
+
+    /// revokes the index now and schedules its reset after the given duration
+    /// (in seconds). Suspending a suspended index sets the new expiration,
+    /// an index that is revoked cannot be suspended. A suspended index must be
+    /// revoked with revoke, a revocation made directly on the list is reset
+    /// when the suspension expires
+    pub fn suspend(
+        &mut self,
+        rl: &mut RevocationList2020,
+        index: u64,
+        duration: u64,
+    ) -> Result<(), CredentialError> {
+        if rl.get(index)? == RevocationStatus::Revoke && !self.is_suspended(index) {
+            return Err(CredentialError::new(&format!(
+                "index {} is revoked, it cannot be suspended",
+                index
+            )));
+        }
+        rl.update_as(
+            RevocationStatus::Revoke,
+            index,
+            SCHEDULER_ACTOR,
+            "suspension",
+        )?;
+        self.lift_suspension(index);
+        let at = self.clock.now().saturating_add(duration);
+        let id = self.schedule(RevocationStatus::Reset, index, at, "suspension expired");
+        self.suspensions.insert(index, id);
+        Ok(())
+    }
+
+    /// revokes the index now, if the index is suspended the revocation
+    /// is permanent and the suspension will not reset it
+    pub fn revoke(
+        &mut self,
+        rl: &mut RevocationList2020,
+        index: u64,
+        reason: &str,
+    ) -> Result<(), CredentialError> {
+        rl.update_as(RevocationStatus::Revoke, index, SCHEDULER_ACTOR, reason)?;
+        self.lift_suspension(index);
+        Ok(())
+    }
+
+    // lift_suspension drops the suspension of the index and its pending reset,
+    // the other transitions of the index are kept
+    fn lift_suspension(&mut self, index: u64) {
+        if let Some(id) = self.suspensions.remove(&index) {
+            self.pending.retain(|p| p.id != id);
+        }
+    }
+
+    /// removes all the pending transitions for the index, including the
+    /// end of its suspension, returning how many have been removed
+    pub fn cancel(&mut self, index: u64) -> usize {
+        let before = self.pending.len();
+        self.pending.retain(|p| p.index != index);
+        self.suspensions.remove(&index);
+        before - self.pending.len()
+    }
+
+    /// applies to the list all the transitions that are due at the current time
+    /// as a single mutation, returning the ones that changed the status of an index.
+    /// A scheduled revocation of a suspended index makes it permanent. If a transition
+    /// cannot be applied nothing is changed and the transitions stay pending
+    pub fn run(
+        &mut self,
+        rl: &mut RevocationList2020,
+    ) -> Result<Vec<ScheduledTransition>, CredentialError> {
+        let now = self.clock.now();
+        let due = self.pending.partition_point(|p| p.at <= now);
+        for t in &self.pending[..due] {
+            rl.check_bounds(t.index)?;
+        }
+
+        let mut suspensions = self.suspensions.clone();
+        // the suspensions ended by a transition, whose expiration is dropped
+        let mut lifted = Vec::new();
+        // the status of the indexes after the transitions applied so far
+        let mut status = BTreeMap::new();
+        let mut changed = Vec::new();
+        for t in &self.pending[..due] {
+            match t.action {
+                RevocationStatus::Revoke => {
+                    if let Some(id) = suspensions.remove(&t.index) {
+                        lifted.push(id);
+                    }
+                }
+                RevocationStatus::Reset => {
+                    if lifted.contains(&t.id) {
+                        continue;
+                    }
+                    // a reset ends the suspension, whenever it expires
+                    if let Some(id) = suspensions.remove(&t.index) {
+                        lifted.push(id);
+                    }
+                }
+            }
+            let current = match status.get(&t.index) {
+                Some(current) => *current,
+                None => rl.get(t.index)?,
+            };
+            if current != t.action {
+                status.insert(t.index, t.action);
+                changed.push(t.clone());
+            }
+        }
+        rl.apply(
+            changed
+                .iter()
+                .map(|t| (t.action, t.index, SCHEDULER_ACTOR, t.reason.as_str())),
+        )?;
+
+        self.pending.drain(..due);
+        self.pending.retain(|p| !lifted.contains(&p.id));
+        self.suspensions = suspensions;
+        Ok(changed)
+    }
+}
@@ -0,0 +1,256 @@
+use crate::did::PublicKey;
+use crate::CredentialError;
+use p256::ecdsa::signature::Signer as _;
+use sha2::{Digest, Sha256, Sha384};
+
+// The signers of the proofs and the JWTs of the credentials. The library never
+// needs the private key itself, only a Signer that signs bytes with it, so the
+// key can stay in an HSM or a KMS
+
+/// Algorithm is the signature algorithm of a key, named as the JWS algorithms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    /// Ed25519 signatures
+    EdDSA,
+    /// ECDSA signatures with P-256 and SHA-256
+    ES256,
+    /// ECDSA signatures with P-384 and SHA-384
+    ES384,
+}
+
+impl Algorithm {
+    /// returns the JWS name of the algorithm
+    pub fn name(&self) -> &'static str {
+        match self {
+            Algorithm::EdDSA => "EdDSA",
+            Algorithm::ES256 => "ES256",
+            Algorithm::ES384 => "ES384",
+        }
+    }
+
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        [Algorithm::EdDSA, Algorithm::ES256, Algorithm::ES384]
+            .into_iter()
+            .find(|a| a.name() == name)
+    }
+
+    /// returns the algorithm that signs with the key
+    pub fn of(key: &PublicKey) -> Self {
+        match key {
+            PublicKey::Ed25519(_) => Algorithm::EdDSA,
+            PublicKey::P256(_) => Algorithm::ES256,
+            PublicKey::P384(_) => Algorithm::ES384,
+        }
+    }
+}
+
+/// Signer signs the proofs and the JWTs of the credentials with a key it holds
+pub trait Signer {
+    /// returns the id of the verification method of the key, that
+    /// is the verificationMethod of the proofs and the kid of the JWTs
+    fn key_id(&self) -> &str;
+    fn algorithm(&self) -> Algorithm;
+    /// signs the data, the ECDSA signatures are the concatenation of r and s
+    fn sign(&self, data: &[u8]) -> Result<Vec<u8>, CredentialError>;
+}
+
+impl<S: Signer + ?Sized> Signer for &S {
+    fn key_id(&self) -> &str {
+        (*self).key_id()
+    }
+
+    fn algorithm(&self) -> Algorithm {
+        (*self).algorithm()
+    }
+
+    fn sign(&self, data: &[u8]) -> Result<Vec<u8>, CredentialError> {
+        (*self).sign(data)
+    }
+}
+
+/// PrivateKey is a private key held in memory
+#[derive(Clone)]
+pub enum PrivateKey {
+    Ed25519([u8; 32]),
+    P256([u8; 32]),
+    P384([u8; 48]),
+}
+
+fn invalid_private_key(e: impl std::fmt::Display) -> CredentialError {
+    CredentialError::new(&format!("invalid private key: {}", e))
+}
+
+impl PrivateKey {
+    /// returns the public key of the private key
+    pub fn public_key(&self) -> Result<PublicKey, CredentialError> {
+        Ok(match self {
+            PrivateKey::Ed25519(secret) => PublicKey::Ed25519(
+                ed25519_dalek::SigningKey::from_bytes(secret)
+                    .verifying_key()
+                    .to_bytes(),
+            ),
+            PrivateKey::P256(secret) => {
+                let key = p256::SecretKey::from_slice(secret).map_err(invalid_private_key)?;
+                PublicKey::P256(
+                    p256::EncodedPoint::from(key.public_key())
+                        .compress()
+                        .as_bytes()
+                        .to_vec(),
+                )
+            }
+            PrivateKey::P384(secret) => {
+                let key = p384::SecretKey::from_slice(secret).map_err(invalid_private_key)?;
+                PublicKey::P384(
+                    p384::EncodedPoint::from(key.public_key())
+                        .compress()
+                        .as_bytes()
+                        .to_vec(),
+                )
+            }
+        })
+    }
+
+    pub fn algorithm(&self) -> Algorithm {
+        match self {
+            PrivateKey::Ed25519(_) => Algorithm::EdDSA,
+            PrivateKey::P256(_) => Algorithm::ES256,
+            PrivateKey::P384(_) => Algorithm::ES384,
+        }
+    }
+
+    fn sign(&self, data: &[u8]) -> Result<Vec<u8>, CredentialError> {
+        Ok(match self {
+            PrivateKey::Ed25519(secret) => ed25519_dalek::SigningKey::from_bytes(secret)
+                .sign(data)
+                .to_vec(),
+            PrivateKey::P256(secret) => {
+                let key =
+                    p256::ecdsa::SigningKey::from_slice(secret).map_err(invalid_private_key)?;
+                let signature: p256::ecdsa::Signature = key.sign(data);
+                signature.to_vec()
+            }
+            PrivateKey::P384(secret) => {
+                let key =
+                    p384::ecdsa::SigningKey::from_slice(secret).map_err(invalid_private_key)?;
+                let signature: p384::ecdsa::Signature = key.sign(data);
+                signature.to_vec()
+            }
+        })
+    }
+}
+
+/// MemorySigner is a Signer with the private key in memory, for the
+/// tests and for the issuers that don't keep their keys in an HSM
+#[derive(Clone)]
+pub struct MemorySigner {
+    key: PrivateKey,
+    key_id: String,
+}
+
+impl MemorySigner {
+    /// creates a signer with the private key of the verification method key_id
+    pub fn new(key: PrivateKey, key_id: &str) -> Self {
+        MemorySigner {
+            key,
+            key_id: key_id.to_owned(),
+        }
+    }
+}
+
+impl Signer for MemorySigner {
+    fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    fn algorithm(&self) -> Algorithm {
+        self.key.algorithm()
+    }
+
+    fn sign(&self, data: &[u8]) -> Result<Vec<u8>, CredentialError> {
+        self.key.sign(data)
+    }
+}
+
+/// Pkcs11Mechanism is a PKCS#11 signing mechanism
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pkcs11Mechanism {
+    /// CKM_EDDSA, that signs the data
+    Eddsa,
+    /// CKM_ECDSA, that signs the hash of the data
+    Ecdsa,
+}
+
+/// Pkcs11Session is a logged in session of a PKCS#11 token, e.g. the
+/// Pkcs11TokenSession of the pkcs11 feature or a session of the cryptoki crate
+pub trait Pkcs11Session {
+    type Key;
+
+    /// signs the data with the private key object, as C_SignInit and C_Sign
+    fn sign(
+        &self,
+        mechanism: Pkcs11Mechanism,
+        key: &Self::Key,
+        data: &[u8],
+    ) -> Result<Vec<u8>, CredentialError>;
+}
+
+/// Pkcs11Signer is a Signer with the private key in a PKCS#11 token, like
+/// an HSM or SoftHSM. The ECDSA data is hashed here and signed with CKM_ECDSA,
+/// since not every token supports CKM_ECDSA_SHA384
+pub struct Pkcs11Signer<S: Pkcs11Session> {
+    session: S,
+    key: S::Key,
+    key_id: String,
+    algorithm: Algorithm,
+}
+
+impl<S: Pkcs11Session> Pkcs11Signer<S> {
+    /// creates a signer with the private key object of the verification method key_id
+    pub fn new(session: S, key: S::Key, key_id: &str, algorithm: Algorithm) -> Self {
+        Pkcs11Signer {
+            session,
+            key,
+            key_id: key_id.to_owned(),
+            algorithm,
+        }
+    }
+}
+
+impl<S: Pkcs11Session> Signer for Pkcs11Signer<S> {
+    fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    fn sign(&self, data: &[u8]) -> Result<Vec<u8>, CredentialError> {
+        let (signature, size) = match self.algorithm {
+            Algorithm::EdDSA => (
+                self.session.sign(Pkcs11Mechanism::Eddsa, &self.key, data)?,
+                64,
+            ),
+            Algorithm::ES256 => (
+                self.session
+                    .sign(Pkcs11Mechanism::Ecdsa, &self.key, &Sha256::digest(data))?,
+                64,
+            ),
+            Algorithm::ES384 => (
+                self.session
+                    .sign(Pkcs11Mechanism::Ecdsa, &self.key, &Sha384::digest(data))?,
+                96,
+            ),
+        };
+        // the tokens return r and s, a DER signature would not verify
+        if signature.len() != size {
+            return Err(CredentialError::new(&format!(
+                "the token returned a {} signature of {} bytes, expected {}",
+                self.algorithm.name(),
+                signature.len(),
+                size
+            )));
+        }
+        Ok(signature)
+    }
+}
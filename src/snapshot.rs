@@ -0,0 +1,139 @@
+use crate::{
+    unix_now, Compression, CredentialError, RevocationList2020, REVOCATION_LIST_2020_TYPE,
+};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Snapshot is the state of a revocation list at a given version
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    #[serde(rename = "id")]
+    pub id: String,
+    #[serde(rename = "version")]
+    pub version: u64,
+    /// unix timestamp (in seconds) of when the snapshot was taken
+    #[serde(rename = "timestamp")]
+    pub timestamp: u64,
+    #[serde(rename = "encodedList")]
+    pub encoded_list: String,
+}
+
+impl Snapshot {
+    /// restores the revocation list from the snapshot, including its version
+    pub fn to_list(&self) -> Result<RevocationList2020, CredentialError> {
+        if self.id.trim().is_empty() {
+            return Err(CredentialError::new("revocation list id cannot be empty"));
+        }
+        Ok(RevocationList2020 {
+            id: self.id.to_owned(),
+            typ: String::from(REVOCATION_LIST_2020_TYPE),
+            encoded_list: self.encoded_list.to_owned(),
+            bit_set: RevocationList2020::unpack(&self.encoded_list)?,
+            journal: None,
+            version: self.version,
+            metadata: None,
+            compression: Compression::Default,
+        })
+    }
+}
+
+/// SnapshotStore is a storage backend for the versions of revocation lists
+pub trait SnapshotStore {
+    /// stores a snapshot and returns its version, that is the version of the
+    /// snapshot unless the store already has it or a later one, then the snapshot
+    /// is stored as the next version of the list. A snapshot with the same bitset
+    /// as the latest version, whatever its compression, is not stored again and
+    /// the latest version is returned, unless it is lower than the snapshot version
+    fn save(&mut self, snapshot: Snapshot) -> Result<u64, CredentialError>;
+    /// returns the snapshot of the list with the given id at the given version
+    fn load(&self, id: &str, version: u64) -> Result<Snapshot, CredentialError>;
+    /// returns the stored versions of the list with the given id, in ascending order
+    fn versions(&self, id: &str) -> Result<Vec<u64>, CredentialError>;
+}
+
+/// MemorySnapshotStore keeps the snapshots in memory
+#[derive(Debug, Clone, Default)]
+pub struct MemorySnapshotStore {
+    snapshots: BTreeMap<(String, u64), Snapshot>,
+}
+
+impl SnapshotStore for MemorySnapshotStore {
+    fn save(&mut self, snapshot: Snapshot) -> Result<u64, CredentialError> {
+        let latest = self
+            .snapshots
+            .range((snapshot.id.to_owned(), 0)..=(snapshot.id.to_owned(), u64::MAX))
+            .next_back()
+            .map(|(_, s)| s);
+        let version = match latest {
+            // the encoded lists differ with the compression, the bitsets don't
+            Some(s)
+                if s.version >= snapshot.version
+                    && RevocationList2020::unpack(&s.encoded_list)?
+                        == RevocationList2020::unpack(&snapshot.encoded_list)? =>
+            {
+                return Ok(s.version)
+            }
+            Some(s) => snapshot.version.max(s.version + 1),
+            None => snapshot.version,
+        };
+        self.snapshots.insert(
+            (snapshot.id.to_owned(), version),
+            Snapshot {
+                version,
+                ..snapshot
+            },
+        );
+        Ok(version)
+    }
+
+    fn load(&self, id: &str, version: u64) -> Result<Snapshot, CredentialError> {
+        self.snapshots
+            .get(&(id.to_owned(), version))
+            .cloned()
+            .ok_or_else(|| {
+                CredentialError::new(&format!("version {} of {} not found", version, id))
+            })
+    }
+
+    fn versions(&self, id: &str) -> Result<Vec<u64>, CredentialError> {
+        Ok(self
+            .snapshots
+            .keys()
+            .filter(|(k, _)| k == id)
+            .map(|(_, v)| *v)
+            .collect())
+    }
+}
+
+impl RevocationList2020 {
+    /// takes a snapshot of the current version of the list
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            id: self.id.to_owned(),
+            version: self.version,
+            timestamp: unix_now(),
+            encoded_list: self.encoded_list.to_owned(),
+        }
+    }
+
+    /// saves a snapshot of the list in the store and takes the version assigned
+    /// by the store, so that a list parsed from JSON, whose version starts again
+    /// from 0, doesn't clash with the versions already stored
+    pub fn save(&mut self, store: &mut impl SnapshotStore) -> Result<u64, CredentialError> {
+        self.version = store.save(self.snapshot())?;
+        Ok(self.version)
+    }
+
+    /// restores the status of the list to a version taken from the store.
+    /// The rollback is itself a mutation, so the version keeps increasing
+    /// and the changes are recorded in the journal, if enabled
+    pub fn rollback(
+        &mut self,
+        store: &impl SnapshotStore,
+        version: u64,
+    ) -> Result<(), CredentialError> {
+        let target = store.load(&self.id, version)?.to_list()?;
+        let diff = self.diff(&target)?;
+        self.apply_diff(diff, &format!("rollback to version {}", version))
+    }
+}
@@ -0,0 +1,212 @@
+use crate::issuer::issuer_id;
+use crate::{
+    CredentialError, CredentialStatus, StatusEntry, StatusPurpose, REVOCATION_LIST_2020_STATUS_TYPE,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize as _, Deserializer};
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+
+const STATUS_LIST_2021_ENTRY_TYPE: &str = "StatusList2021Entry";
+const BITSTRING_STATUS_LIST_ENTRY_TYPE: &str = "BitstringStatusListEntry";
+
+// the specs define the index as a string, but numbers are commonly used too
+fn index_from_any<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Index {
+        Number(u64),
+        String(String),
+    }
+    match Index::deserialize(deserializer)? {
+        Index::Number(n) => Ok(n),
+        Index::String(s) => s.parse().map_err(serde::de::Error::custom),
+    }
+}
+
+/// RevocationList2020Status is the status entry of the RevocationList2020 spec,
+/// see https://w3c-ccg.github.io/vc-status-rl-2020/#revocationlist2020status
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RevocationList2020Status {
+    #[serde(rename = "id", default)]
+    pub id: String,
+    #[serde(rename = "type")]
+    pub typ: String,
+    #[serde(rename = "revocationListIndex", deserialize_with = "index_from_any")]
+    pub revocation_list_index: u64,
+    #[serde(rename = "revocationListCredential")]
+    pub revocation_list_credential: String,
+    /// RevocationList2020 entries are for revocation unless stated otherwise
+    #[serde(
+        rename = "statusPurpose",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub status_purpose: Option<String>,
+}
+
+impl CredentialStatus for RevocationList2020Status {
+    fn coordinates(&self) -> (String, u64) {
+        (
+            self.revocation_list_credential.to_owned(),
+            self.revocation_list_index,
+        )
+    }
+
+    fn type_def(&self) -> (String, String) {
+        (self.id.to_owned(), self.typ.to_owned())
+    }
+
+    fn purpose(&self) -> StatusPurpose {
+        self.status_purpose
+            .as_deref()
+            .map(StatusPurpose::from)
+            .unwrap_or(StatusPurpose::Revocation)
+    }
+}
+
+/// StatusList2021Entry is the status entry of the StatusList2021 spec,
+/// see https://www.w3.org/TR/2023/WD-vc-status-list-20230427/#statuslist2021entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusList2021Entry {
+    #[serde(rename = "id", default)]
+    pub id: String,
+    #[serde(rename = "type")]
+    pub typ: String,
+    #[serde(rename = "statusPurpose")]
+    pub status_purpose: String,
+    #[serde(rename = "statusListIndex", deserialize_with = "index_from_any")]
+    pub status_list_index: u64,
+    #[serde(rename = "statusListCredential")]
+    pub status_list_credential: String,
+}
+
+impl CredentialStatus for StatusList2021Entry {
+    fn coordinates(&self) -> (String, u64) {
+        (
+            self.status_list_credential.to_owned(),
+            self.status_list_index,
+        )
+    }
+
+    fn type_def(&self) -> (String, String) {
+        (self.id.to_owned(), self.typ.to_owned())
+    }
+
+    fn purpose(&self) -> StatusPurpose {
+        StatusPurpose::from(self.status_purpose.as_str())
+    }
+}
+
+/// BitstringStatusListEntry is the status entry of the Bitstring Status List spec,
+/// see https://www.w3.org/TR/vc-bitstring-status-list/#bitstringstatuslistentry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BitstringStatusListEntry {
+    #[serde(rename = "id", default)]
+    pub id: String,
+    #[serde(rename = "type")]
+    pub typ: String,
+    #[serde(rename = "statusPurpose")]
+    pub status_purpose: String,
+    #[serde(rename = "statusListIndex", deserialize_with = "index_from_any")]
+    pub status_list_index: u64,
+    #[serde(rename = "statusListCredential")]
+    pub status_list_credential: String,
+    #[serde(
+        rename = "statusSize",
+        skip_serializing_if = "Option::is_none",
+        default
+    )]
+    pub status_size: Option<u64>,
+}
+
+impl CredentialStatus for BitstringStatusListEntry {
+    fn coordinates(&self) -> (String, u64) {
+        (
+            self.status_list_credential.to_owned(),
+            self.status_list_index,
+        )
+    }
+
+    fn type_def(&self) -> (String, String) {
+        (self.id.to_owned(), self.typ.to_owned())
+    }
+
+    fn purpose(&self) -> StatusPurpose {
+        StatusPurpose::from(self.status_purpose.as_str())
+    }
+}
+
+// parse_entry parses a single status entry according to its type,
+// the entries of unknown types are skipped
+fn parse_entry(entry: &Value) -> Result<Option<StatusEntry>, CredentialError> {
+    fn parse<T: CredentialStatus + DeserializeOwned>(
+        entry: &Value,
+    ) -> Result<Option<StatusEntry>, CredentialError> {
+        serde_json::from_value::<T>(entry.clone())
+            .map(|s| s.status_entries().pop())
+            .map_err(|e| CredentialError::new(&e.to_string()))
+    }
+    match entry["type"].as_str().unwrap_or_default() {
+        REVOCATION_LIST_2020_STATUS_TYPE => parse::<RevocationList2020Status>(entry),
+        STATUS_LIST_2021_ENTRY_TYPE => parse::<StatusList2021Entry>(entry),
+        BITSTRING_STATUS_LIST_ENTRY_TYPE => parse::<BitstringStatusListEntry>(entry),
+        _ => Ok(None),
+    }
+}
+
+/// locates the credentialStatus in a verifiable credential and parses its entries,
+/// skipping the ones of unsupported types. The value can also be the
+/// credentialStatus itself, either a single entry or an array
+pub fn find_status_entries(vc: &Value) -> Result<Vec<StatusEntry>, CredentialError> {
+    let status = vc.get("credentialStatus").unwrap_or(vc);
+    let entries = match status {
+        Value::Array(entries) => entries.iter().map(parse_entry).collect(),
+        Value::Object(_) => parse_entry(status).map(|e| vec![e]),
+        _ => Err(CredentialError::new(
+            "the credential has no credentialStatus",
+        )),
+    }?;
+    let entries: Vec<StatusEntry> = entries.into_iter().flatten().collect();
+    if entries.is_empty() {
+        return Err(CredentialError::new(
+            "the credential has no supported credentialStatus",
+        ));
+    }
+    Ok(entries)
+}
+
+/// A raw JSON verifiable credential can be checked directly, against the entry
+/// that refers to the list being checked. The coordinates are the ones of its first
+/// RevocationList2020Status entry, or of its first entry if there are none.
+/// A value without a valid credentialStatus is rejected by the checks
+impl CredentialStatus for Value {
+    fn coordinates(&self) -> (String, u64) {
+        primary_entry(self)
+            .map(|e| (e.list, e.index))
+            .unwrap_or_default()
+    }
+
+    fn type_def(&self) -> (String, String) {
+        primary_entry(self)
+            .map(|e| (e.id, e.typ))
+            .unwrap_or_default()
+    }
+
+    fn status_entries(&self) -> Vec<StatusEntry> {
+        find_status_entries(self).unwrap_or_default()
+    }
+
+    fn issuer(&self) -> Option<String> {
+        self.get("issuer").and_then(issuer_id)
+    }
+}
+
+fn primary_entry(vc: &Value) -> Option<StatusEntry> {
+    let mut entries = find_status_entries(vc).ok()?;
+    let pos = entries
+        .iter()
+        .position(|e| e.typ == REVOCATION_LIST_2020_STATUS_TYPE)
+        .unwrap_or(0);
+    Some(entries.swap_remove(pos))
+}
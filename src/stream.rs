@@ -0,0 +1,180 @@
+use crate::{bitstring, CredentialError, RevocationStatus, MAX_BITSTRING_SIZE_KB};
+use base64::read::DecoderReader;
+use base64::write::EncoderWriter;
+use base64::STANDARD;
+use miniz_oxide::deflate::stream::deflate;
+use miniz_oxide::inflate::stream::{inflate, InflateState};
+use miniz_oxide::{DataFormat, MZError, MZFlush, MZStatus};
+use std::io::{self, Read, Write};
+
+// The streaming versions of the encoding of the bitstring, for lists that are
+// too large to be kept in memory twice. They use the same zlib compressor of
+// Bitstring::encode, so the output is the same
+
+// the size of the chunks read from the input and of the output buffers
+const CHUNK_SIZE: usize = 32 * 1024;
+
+fn io_error(e: io::Error) -> CredentialError {
+    CredentialError::new(&format!("error streaming the encoded list: {}", e))
+}
+
+// read_chunk reads from input into the buffer, retrying on interruptions,
+// it returns 0 only at the end of the input
+fn read_chunk<R: Read>(input: &mut R, buf: &mut [u8]) -> Result<usize, CredentialError> {
+    loop {
+        match input.read(buf) {
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            r => return r.map_err(io_error),
+        }
+    }
+}
+
+/// reads the bitstring from input and writes its encodedList to output,
+/// returning the number of bytes of the bitstring
+pub fn encode_stream<R: Read, W: Write>(
+    input: &mut R,
+    output: &mut W,
+) -> Result<u64, CredentialError> {
+    let mut b64 = EncoderWriter::new(output, STANDARD);
+    let mut compressor = bitstring::zlib_compressor();
+    let (mut chunk, mut out) = (vec![0u8; CHUNK_SIZE], vec![0u8; CHUNK_SIZE]);
+    let mut n = 0;
+    loop {
+        let read = read_chunk(input, &mut chunk)?;
+        n += read as u64;
+        let flush = match read {
+            0 => MZFlush::Finish,
+            _ => MZFlush::None,
+        };
+        let mut data = &chunk[..read];
+        loop {
+            let res = deflate(&mut compressor, data, &mut out, flush);
+            data = &data[res.bytes_consumed..];
+            b64.write_all(&out[..res.bytes_written]).map_err(io_error)?;
+            match res.status {
+                Ok(MZStatus::StreamEnd) => {
+                    b64.finish().map_err(io_error)?;
+                    return Ok(n);
+                }
+                Ok(_) if data.is_empty() && flush == MZFlush::None => break,
+                Ok(_) => continue,
+                Err(e) => {
+                    return Err(CredentialError::new(&format!(
+                        "error compressing the encoded list: {:?}",
+                        e
+                    )))
+                }
+            }
+        }
+    }
+}
+
+// Inflater reads the zlib stream from the inner reader and decompresses it,
+// a truncated or corrupted stream is an InvalidData error
+struct Inflater<R: Read> {
+    inner: R,
+    state: Box<InflateState>,
+    input: Vec<u8>,
+    pos: usize,
+    len: usize,
+    done: bool,
+}
+
+impl<R: Read> Inflater<R> {
+    fn new(inner: R) -> Self {
+        Inflater {
+            inner,
+            state: InflateState::new_boxed(DataFormat::Zlib),
+            input: vec![0u8; CHUNK_SIZE],
+            pos: 0,
+            len: 0,
+            done: false,
+        }
+    }
+}
+
+impl<R: Read> Read for Inflater<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.done || buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            let eof = self.pos == self.len && {
+                self.len = self.inner.read(&mut self.input)?;
+                self.pos = 0;
+                self.len == 0
+            };
+            let res = inflate(
+                &mut self.state,
+                &self.input[self.pos..self.len],
+                buf,
+                MZFlush::None,
+            );
+            self.pos += res.bytes_consumed;
+            match res.status {
+                Ok(MZStatus::StreamEnd) => {
+                    self.done = true;
+                    return Ok(res.bytes_written);
+                }
+                Ok(_) if res.bytes_written > 0 => return Ok(res.bytes_written),
+                Ok(_) => continue,
+                Err(MZError::Buf) if !eof => continue,
+                Err(MZError::Buf) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "the zlib stream is truncated",
+                    ))
+                }
+                Err(e) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("invalid zlib stream: {:?}", e),
+                    ))
+                }
+            }
+        }
+    }
+}
+
+/// reads an encodedList from input and writes the decoded bitstring to output,
+/// returning the number of bytes of the bitstring, the bitstring cannot exceed
+/// the maximum size of the spec
+pub fn decode_stream<R: Read, W: Write>(
+    input: &mut R,
+    output: &mut W,
+) -> Result<u64, CredentialError> {
+    let max = (MAX_BITSTRING_SIZE_KB * 1024) as u64;
+    let zlib = Inflater::new(DecoderReader::new(input, STANDARD));
+    // one byte over the limit is enough to tell that the limit is exceeded
+    let size = io::copy(&mut zlib.take(max + 1), output).map_err(io_error)?;
+    if size > max {
+        return Err(CredentialError::new(&format!(
+            "the bitstring of the encoded list exceeds the maximum size of {}kb",
+            MAX_BITSTRING_SIZE_KB
+        )));
+    }
+    Ok(size)
+}
+
+/// returns the status of the index reading the encodedList from input, the list
+/// is decompressed only up to the byte that contains the index
+pub fn lookup_status<R: Read>(
+    input: &mut R,
+    index: u64,
+) -> Result<RevocationStatus, CredentialError> {
+    let mut zlib = Inflater::new(DecoderReader::new(input, STANDARD));
+    let pos = index / 8;
+    let skipped = io::copy(&mut zlib.by_ref().take(pos), &mut io::sink()).map_err(io_error)?;
+    let mut byte = [0u8; 1];
+    if skipped < pos || zlib.read(&mut byte).map_err(io_error)? == 0 {
+        return Err(CredentialError::new(&format!(
+            "max indexable element is {}, provided index {} is out of range",
+            skipped * 8,
+            index,
+        )));
+    }
+    match byte[0] & (1 << (index % 8)) {
+        0 => Ok(RevocationStatus::Reset),
+        _ => Ok(RevocationStatus::Revoke),
+    }
+}
@@ -0,0 +1,296 @@
+use crate::{
+    CredentialError, CredentialStatus, RevocationList2020, RevocationList2020Credential,
+    RevocationStatus, SimpleCredential,
+};
+use std::str::FromStr;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+pub fn init_panic_hook() {
+    #[cfg(feature = "console_error_panic_hook")]
+    console_error_panic_hook::set_once();
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &'static str = r#"
+export interface RevocationList2020 {
+  id: string;
+  type: "RevocationList2020";
+  encodedList: string;
+}
+
+export interface RevocationList2020Credential {
+  id: string;
+  type: string[];
+  credentialSubject: RevocationList2020;
+  [key: string]: unknown;
+}
+
+export interface RevocationList2020Status {
+  id?: string;
+  type: "RevocationList2020Status";
+  revocationListIndex: number | string;
+  revocationListCredential: string;
+}
+
+export interface Credential {
+  credentialStatus: RevocationList2020Status | { type: string; [key: string]: unknown }[];
+  [key: string]: unknown;
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "RevocationList2020Credential | RevocationList2020 | string")]
+    pub type RevocationListInput;
+
+    #[wasm_bindgen(typescript_type = "RevocationList2020")]
+    pub type RevocationListObject;
+
+    #[wasm_bindgen(typescript_type = "Credential | string")]
+    pub type CredentialInput;
+}
+
+// from_js deserializes a value that is either a JSON string or a plain javascript object
+fn from_js<T: serde::de::DeserializeOwned>(value: &JsValue) -> Result<T, String> {
+    match value.as_string() {
+        Some(s) => serde_json::from_str(&s).map_err(|e| e.to_string()),
+        None => serde_wasm_bindgen::from_value(value.clone()).map_err(|e| e.to_string()),
+    }
+}
+
+// JsRevocationList is a revocation list received from javascript,
+// either as a full verifiable credential or as the bare list
+enum JsRevocationList {
+    List(RevocationList2020),
+    Credential(RevocationList2020Credential),
+}
+
+impl JsRevocationList {
+    fn is_revoked(&self, credential: &impl CredentialStatus) -> Result<bool, CredentialError> {
+        match self {
+            JsRevocationList::List(rl) => rl.is_revoked(credential),
+            JsRevocationList::Credential(rc) => rc.is_revoked(credential),
+        }
+    }
+
+    fn revoke(&mut self, credential: &impl CredentialStatus) -> Result<(), CredentialError> {
+        match self {
+            JsRevocationList::List(rl) => rl.revoke(credential),
+            JsRevocationList::Credential(rc) => rc.revoke(credential),
+        }
+    }
+
+    fn reset(&mut self, credential: &impl CredentialStatus) -> Result<(), CredentialError> {
+        match self {
+            JsRevocationList::List(rl) => rl.reset(credential),
+            JsRevocationList::Credential(rc) => rc.reset(credential),
+        }
+    }
+
+    fn into_list(self) -> RevocationList2020 {
+        match self {
+            JsRevocationList::List(rl) => rl,
+            JsRevocationList::Credential(rc) => rc.list().to_owned(),
+        }
+    }
+
+    // to_js returns the revocation list in the same shape of the input,
+    // that is a JSON string for a string input and a plain object otherwise
+    fn to_js(&self, shape: &JsValue) -> Result<JsValue, JsValue> {
+        let value = match (self, shape.is_string()) {
+            (JsRevocationList::List(rl), true) => Ok(JsValue::from(rl.to_string())),
+            (JsRevocationList::Credential(rc), true) => Ok(JsValue::from(rc.to_string())),
+            (JsRevocationList::List(rl), false) => serde_wasm_bindgen::to_value(rl),
+            (JsRevocationList::Credential(rc), false) => serde_wasm_bindgen::to_value(rc),
+        };
+        value.map_err(|err| {
+            JsValue::from(&format!("error serializing the revocation list: {}", err))
+        })
+    }
+}
+
+fn parse_js_revocation_list(value: &JsValue) -> Result<JsRevocationList, JsValue> {
+    let parse = || -> Result<JsRevocationList, String> {
+        let v = from_js::<serde_json::Value>(value)?;
+        if v.get("credentialSubject").is_some() {
+            let rc = serde_json::from_value::<RevocationList2020Credential>(v)
+                .map_err(|e| e.to_string())?;
+            return rc
+                .decode()
+                .map(JsRevocationList::Credential)
+                .map_err(|e| e.to_string());
+        }
+        let rl = serde_json::from_value::<RevocationList2020>(v).map_err(|e| e.to_string())?;
+        rl.decode()
+            .map(JsRevocationList::List)
+            .map_err(|e| e.to_string())
+    };
+    parse().map_err(|err| JsValue::from(&format!("error parsing the revocation list: {}", err)))
+}
+
+fn parse_js_credential(value: &JsValue) -> Result<SimpleCredential, JsValue> {
+    from_js::<SimpleCredential>(value)
+        .map_err(|err| JsValue::from(&format!("error parsing the input credential: {}", err)))
+}
+
+#[wasm_bindgen]
+#[allow(clippy::to_string_in_format_args)]
+pub fn is_revoked(
+    revocation_list_credential: &RevocationListInput,
+    subject_credential: &CredentialInput,
+) -> Result<bool, JsValue> {
+    let rl = parse_js_revocation_list(revocation_list_credential)?;
+    let cr = parse_js_credential(subject_credential)?;
+    rl.is_revoked(&cr).map_err(|err| {
+        JsValue::from(&format!(
+            "error checking the revocation status: {}",
+            err.to_string()
+        ))
+    })
+}
+
+#[wasm_bindgen]
+#[allow(clippy::to_string_in_format_args)]
+pub fn revoke_credential(
+    revocation_list_credential: &RevocationListInput,
+    subject_credential: &CredentialInput,
+) -> Result<RevocationListInput, JsValue> {
+    let mut rl = parse_js_revocation_list(revocation_list_credential)?;
+    let cr = parse_js_credential(subject_credential)?;
+    rl.revoke(&cr).map_err(|err| {
+        JsValue::from(&format!(
+            "error checking the revocation status: {}",
+            err.to_string()
+        ))
+    })?;
+    rl.to_js(revocation_list_credential)
+        .map(|v| v.unchecked_into())
+}
+
+#[wasm_bindgen]
+#[allow(clippy::to_string_in_format_args)]
+pub fn reset_credential(
+    revocation_list_credential: &RevocationListInput,
+    subject_credential: &CredentialInput,
+) -> Result<RevocationListInput, JsValue> {
+    let mut rl = parse_js_revocation_list(revocation_list_credential)?;
+    let cr = parse_js_credential(subject_credential)?;
+    rl.reset(&cr).map_err(|err| {
+        JsValue::from(&format!(
+            "error checking the revocation status: {}",
+            err.to_string()
+        ))
+    })?;
+    rl.to_js(revocation_list_credential)
+        .map(|v| v.unchecked_into())
+}
+
+/// RevocationList wraps a RevocationList2020 to keep
+/// a decoded revocation list in memory on the javascript side
+#[wasm_bindgen(js_name = RevocationList)]
+pub struct WasmRevocationList {
+    rl: RevocationList2020,
+}
+
+#[wasm_bindgen(js_class = RevocationList)]
+impl WasmRevocationList {
+    #[wasm_bindgen(constructor)]
+    pub fn new(id: &str, size: usize) -> Result<WasmRevocationList, JsValue> {
+        RevocationList2020::new(id, size)
+            .map(|rl| WasmRevocationList { rl })
+            .map_err(|err| JsValue::from(&format!("error creating the revocation list: {}", err)))
+    }
+
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(data: &str) -> Result<WasmRevocationList, JsValue> {
+        RevocationList2020::from_str(data)
+            .map(|rl| WasmRevocationList { rl })
+            .map_err(|err| JsValue::from(&format!("error parsing the revocation list: {}", err)))
+    }
+
+    #[wasm_bindgen(js_name = fromObject)]
+    pub fn from_object(value: &RevocationListInput) -> Result<WasmRevocationList, JsValue> {
+        parse_js_revocation_list(value).map(|rl| WasmRevocationList { rl: rl.into_list() })
+    }
+
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> String {
+        self.rl.to_string()
+    }
+
+    #[wasm_bindgen(js_name = toObject)]
+    pub fn to_object(&self) -> Result<RevocationListObject, JsValue> {
+        serde_wasm_bindgen::to_value(&self.rl)
+            .map(|v| v.unchecked_into())
+            .map_err(|err| {
+                JsValue::from(&format!("error serializing the revocation list: {}", err))
+            })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.rl.id.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn capacity(&self) -> usize {
+        self.rl.capacity()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.rl.size()
+    }
+
+    pub fn revoke(&mut self, index: u32) -> Result<(), JsValue> {
+        self.update_many(RevocationStatus::Revoke, &[index])
+    }
+
+    pub fn reset(&mut self, index: u32) -> Result<(), JsValue> {
+        self.update_many(RevocationStatus::Reset, &[index])
+    }
+
+    #[wasm_bindgen(js_name = isRevoked)]
+    pub fn is_revoked(&self, index: u32) -> Result<bool, JsValue> {
+        self.rl
+            .get(index.into())
+            .map(|s| s == RevocationStatus::Revoke)
+            .map_err(|err| JsValue::from(&format!("error checking the revocation status: {}", err)))
+    }
+
+    #[wasm_bindgen(js_name = revokeMany)]
+    pub fn revoke_many(&mut self, indexes: Vec<u32>) -> Result<(), JsValue> {
+        self.update_many(RevocationStatus::Revoke, &indexes)
+    }
+
+    #[wasm_bindgen(js_name = resetMany)]
+    pub fn reset_many(&mut self, indexes: Vec<u32>) -> Result<(), JsValue> {
+        self.update_many(RevocationStatus::Reset, &indexes)
+    }
+
+    /// returns the indexes among the given ones that are revoked
+    #[wasm_bindgen(js_name = filterRevoked)]
+    pub fn filter_revoked(&self, indexes: Vec<u32>) -> Result<Vec<u32>, JsValue> {
+        let mut revoked = Vec::new();
+        for i in indexes {
+            if self.is_revoked(i)? {
+                revoked.push(i);
+            }
+        }
+        Ok(revoked)
+    }
+
+    /// returns all the revoked indexes of the list
+    #[wasm_bindgen(js_name = revokedIndexes)]
+    pub fn revoked_indexes(&self) -> Vec<u32> {
+        self.rl.revoked_indexes().map(|i| i as u32).collect()
+    }
+
+    fn update_many(&mut self, action: RevocationStatus, indexes: &[u32]) -> Result<(), JsValue> {
+        let indexes: Vec<u64> = indexes.iter().map(|i| u64::from(*i)).collect();
+        self.rl
+            .update_many(action, &indexes)
+            .map_err(|err| JsValue::from(&format!("error updating the revocation status: {}", err)))
+    }
+}